@@ -0,0 +1,117 @@
+use crate::{age, is_valid_key};
+use anyhow::anyhow;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tar::{Archive, Builder, EntryType, Header};
+
+pub struct ArchiveEntry {
+    pub key: String,
+    pub content: String,
+    pub modified: Option<SystemTime>,
+}
+
+pub enum Protection<'a> {
+    Recipient(&'a str),
+    Passphrase,
+}
+
+pub fn write(path: &Path, entries: &[ArchiveEntry], protection: Protection) -> anyhow::Result<()> {
+    let tar = to_tar(entries)?;
+    match protection {
+        Protection::Recipient(recipient) => age::encrypt(&mut tar.as_slice(), &[recipient], path),
+        Protection::Passphrase => age::encrypt_with_passphrase(&mut tar.as_slice(), path),
+    }
+}
+
+pub fn read(path: &Path, passphrase: bool) -> anyhow::Result<Vec<ArchiveEntry>> {
+    let tar = if passphrase {
+        age::decrypt_with_passphrase(path)?
+    } else {
+        age::decrypt_binary(path)?
+    };
+    from_tar(tar.as_slice())
+}
+
+fn to_tar(entries: &[ArchiveEntry]) -> anyhow::Result<Vec<u8>> {
+    let mut builder = Builder::new(Vec::new());
+    for entry in entries {
+        let mut header = Header::new_gnu();
+        header.set_entry_type(EntryType::Regular);
+        header.set_size(entry.content.len() as u64);
+        header.set_mode(0o600);
+        let modified = entry
+            .modified
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        header.set_mtime(modified.as_secs());
+        builder.append_data(&mut header, &entry.key, entry.content.as_bytes())?;
+    }
+    Ok(builder.into_inner()?)
+}
+
+fn from_tar(tar: &[u8]) -> anyhow::Result<Vec<ArchiveEntry>> {
+    let mut archive = Archive::new(tar);
+    let mut entries = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.header().entry_type() != EntryType::Regular {
+            continue;
+        }
+        let key = entry
+            .path()?
+            .to_str()
+            .ok_or(anyhow!("Encountered invalid UTF-8"))?
+            .replace('\\', "/");
+        if !is_valid_key(&key) {
+            return Err(anyhow!("Invalid key in archive: {key}"));
+        }
+        let modified = entry
+            .header()
+            .mtime()
+            .ok()
+            .filter(|&secs| secs > 0)
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        entries.push(ArchiveEntry {
+            key,
+            content,
+            modified,
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tar_roundtrip() -> anyhow::Result<()> {
+        let modified = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let entries = vec![
+            ArchiveEntry {
+                key: "dir/key".to_string(),
+                content: "pass\nuser: me".to_string(),
+                modified: Some(modified),
+            },
+            ArchiveEntry {
+                key: "other".to_string(),
+                content: "".to_string(),
+                modified: None,
+            },
+        ];
+
+        let read = from_tar(to_tar(&entries)?.as_slice())?;
+
+        assert_eq!(2, read.len());
+        assert_eq!("dir/key", read[0].key);
+        assert_eq!("pass\nuser: me", read[0].content);
+        assert_eq!(Some(modified), read[0].modified);
+        assert_eq!("other", read[1].key);
+        assert_eq!("", read[1].content);
+        assert_eq!(None, read[1].modified);
+        Ok(())
+    }
+}
@@ -2,12 +2,17 @@ pub mod age;
 pub mod gpg;
 
 use anyhow::anyhow;
+use itertools::Itertools;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::fs;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
-use std::path::Path;
-use std::process::{ExitStatus, Output};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output, Stdio};
+use walkdir::WalkDir;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Backend {
     Age,
     Gpg,
@@ -20,22 +25,51 @@ impl Backend {
         store: &Path,
         out_path: &Path,
     ) -> anyhow::Result<()> {
+        let recipients = self.applicable_recipients(store, out_path)?;
         match self {
             Backend::Age => {
-                age::encrypt(&mut content, &self.recipient(store)?, out_path)?;
+                age::encrypt(&mut content, &recipients, out_path)?;
             }
             Backend::Gpg => {
-                gpg::encrypt(&mut content, &self.recipient(store)?, out_path)?;
+                gpg::encrypt(&mut content, &recipients, out_path)?;
             }
         }
         Ok(())
     }
 
+    /// Encrypts the content over an existing file, which is kept if the encryption fails
+    pub fn encrypt_replacing(
+        &self,
+        content: impl Read,
+        store: &Path,
+        location: &Path,
+    ) -> anyhow::Result<()> {
+        let mut bak = location.to_path_buf();
+        bak.as_mut_os_string().push(".bak");
+        fs::rename(location, &bak)?;
+        match self.encrypt(content, store, location) {
+            Ok(()) => Ok(fs::remove_file(bak)?),
+            Err(err) => {
+                fs::rename(&bak, location)?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Errors of the backend are wrapped in a [DecryptError]
     pub fn decrypt(path: &Path) -> anyhow::Result<String> {
+        let decrypted = match Backend::detect(path)? {
+            Backend::Age => age::decrypt(path),
+            Backend::Gpg => gpg::decrypt(path),
+        };
+        Ok(decrypted.map_err(DecryptError)?)
+    }
+
+    pub fn detect(path: &Path) -> anyhow::Result<Backend> {
         if is_age_encrypted(path)? {
-            age::decrypt(path)
+            Ok(Backend::Age)
         } else {
-            gpg::decrypt(path)
+            Ok(Backend::Gpg)
         }
     }
 
@@ -61,12 +95,208 @@ impl Backend {
         }
     }
 
-    fn recipient(&self, dir: &Path) -> anyhow::Result<String> {
-        let recipient_file = dir.join(self.recipient_file_name());
-        read_first_line(&recipient_file)
+    /// The recipients in the recipient file of the directory
+    pub fn recipients(&self, dir: &Path) -> anyhow::Result<Vec<String>> {
+        read_recipients(&dir.join(self.recipient_file_name()))
+    }
+
+    /// The recipient file which applies to a location in the store: the nearest one
+    /// in the directory of the location or one of its parents up to the store
+    pub fn recipient_file(&self, store: &Path, location: &Path) -> Option<PathBuf> {
+        location
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(store))
+            .map(|dir| dir.join(self.recipient_file_name()))
+            .find(|file| file.is_file())
+    }
+
+    /// All recipient files of the backend in the store and its subdirectories
+    pub fn recipient_files(&self, store: &Path) -> anyhow::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let walk_dir = WalkDir::new(store).into_iter().filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || !entry.file_name().to_string_lossy().starts_with('.')
+        });
+        for entry in walk_dir {
+            let entry = entry?;
+            if entry.file_type().is_file() && entry.file_name() == self.recipient_file_name() {
+                files.push(entry.into_path());
+            }
+        }
+        Ok(files)
+    }
+
+    /// The recipients of the [recipient file](Backend::recipient_file) which applies to the location
+    pub fn applicable_recipients(
+        &self,
+        store: &Path,
+        location: &Path,
+    ) -> anyhow::Result<Vec<String>> {
+        let file = self.recipient_file(store, location).ok_or_else(|| {
+            anyhow!(
+                "No {} applies to {}",
+                self.recipient_file_name(),
+                location.display()
+            )
+        })?;
+        read_recipients(&file)
+    }
+
+    /// Checks whether the file is encrypted to exactly the given recipients, as far as the format reveals:
+    /// gpg files name the key ids of their recipients, age files only the number and type of theirs
+    pub fn check_addressed(
+        &self,
+        path: &Path,
+        recipients: &[String],
+    ) -> anyhow::Result<Option<Misaddressed>> {
+        match self {
+            Backend::Age => {
+                let mut stanzas = age::recipient_stanzas(path)?;
+                if stanzas.len() != recipients.len() {
+                    return Ok(Some(Misaddressed::RecipientCount {
+                        expected: recipients.len(),
+                        actual: stanzas.len(),
+                    }));
+                }
+                for recipient in recipients {
+                    let Some(stanza_type) = age::stanza_type(recipient) else {
+                        continue;
+                    };
+                    match stanzas.iter().position(|s| s == stanza_type) {
+                        Some(index) => {
+                            stanzas.swap_remove(index);
+                        }
+                        None => return Ok(Some(Misaddressed::NotToRecipient(recipient.clone()))),
+                    }
+                }
+                Ok(None)
+            }
+            Backend::Gpg => {
+                let encrypted_to = gpg::encrypted_to(path)?;
+                let mut known = Vec::new();
+                for recipient in recipients {
+                    let key_ids = gpg::key_ids(recipient)?;
+                    if !encrypted_to.iter().any(|id| key_ids.contains(id)) {
+                        return Ok(Some(Misaddressed::NotToRecipient(recipient.clone())));
+                    }
+                    known.extend(key_ids);
+                }
+                let unknown: Vec<_> = encrypted_to
+                    .into_iter()
+                    .filter(|id| !known.contains(id))
+                    .collect();
+                Ok((!unknown.is_empty()).then_some(Misaddressed::UnknownKeys(unknown)))
+            }
+        }
+    }
+
+    /// Fails if the recipient is malformed or, for gpg, has no public key in the keyring
+    pub fn check_recipient(&self, recipient: &str) -> anyhow::Result<()> {
+        match self {
+            Backend::Age => age::check_recipient(recipient),
+            Backend::Gpg => gpg::check_recipient(recipient),
+        }
+    }
+
+    /// The first line the binary of the backend prints for --version
+    pub fn version(&self) -> anyhow::Result<String> {
+        let output = Command::new(self.display_name())
+            .arg("--version")
+            .stdin(Stdio::null())
+            .output()?;
+        let version = output_to_result(output)?;
+        Ok(version.lines().next().unwrap_or_default().to_string())
+    }
+}
+
+/// How an encrypted file deviates from the recipient file which applies to it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Misaddressed {
+    /// The file is not encrypted to the recipient
+    NotToRecipient(String),
+    /// The file is additionally encrypted to keys which do not belong to any recipient
+    UnknownKeys(Vec<String>),
+    /// The file is encrypted to a different number of recipients
+    RecipientCount { expected: usize, actual: usize },
+}
+
+impl Display for Misaddressed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Misaddressed::NotToRecipient(recipient) => write!(f, "not encrypted to {recipient}"),
+            Misaddressed::UnknownKeys(keys) => {
+                write!(f, "also encrypted to the keys {}", keys.join(", "))
+            }
+            Misaddressed::RecipientCount { expected, actual } => {
+                write!(f, "encrypted to {actual} recipients instead of {expected}")
+            }
+        }
+    }
+}
+
+/// The backend failed to decrypt a file, for example because no matching identity is available
+pub struct DecryptError(anyhow::Error);
+
+impl Display for DecryptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Debug for DecryptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Error for DecryptError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
     }
 }
 
+/// Reads a recipient file with one recipient per line; empty lines and lines starting with # are skipped
+pub fn read_recipients(file: &Path) -> anyhow::Result<Vec<String>> {
+    let recipients: Vec<_> = BufReader::new(File::open(file)?)
+        .lines()
+        .map_ok(|line| line.trim().to_string())
+        .filter_ok(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Result<_, _>>()?;
+    if recipients.is_empty() {
+        Err(anyhow!("{} contains no recipient", file.display()))
+    } else {
+        Ok(recipients)
+    }
+}
+
+pub fn write_recipients(file: &Path, recipients: &[String]) -> anyhow::Result<()> {
+    let mut content = recipients.join("\n");
+    content.push('\n');
+    fs::write(file, content)?;
+    Ok(())
+}
+
+/// Edits the content of a recipient file line by line, keeping comments and empty lines:
+/// the lines of the removed recipients are dropped, and the added recipients take the place
+/// of the first of them or are appended if none was removed
+pub fn edit_recipients(content: &str, removed: &[String], added: &[String]) -> String {
+    let mut added = Some(added);
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        if removed.iter().any(|r| r == line.trim()) {
+            lines.extend(added.take().into_iter().flatten().map(String::as_str));
+        } else {
+            lines.push(line);
+        }
+    }
+    lines.extend(added.into_iter().flatten().map(String::as_str));
+    let mut edited = lines.join("\n");
+    edited.push('\n');
+    edited
+}
+
 fn is_age_encrypted(path: &Path) -> anyhow::Result<bool> {
     let first_line = read_first_line(path)?;
     Ok(
@@ -108,3 +338,24 @@ fn output_to_result(mut output: Output) -> anyhow::Result<String> {
         Err(anyhow!(String::from_utf8(output.stderr)?))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "# team\nage1first\n\n# me\nage1second\n";
+
+    #[test]
+    fn test_edit_recipients() {
+        let add = edit_recipients(CONTENT, &[], &["age1third".to_string()]);
+        assert_eq!("# team\nage1first\n\n# me\nage1second\nage1third\n", add);
+        let remove = edit_recipients(CONTENT, &["age1first".to_string()], &[]);
+        assert_eq!("# team\n\n# me\nage1second\n", remove);
+        let replace = edit_recipients(
+            CONTENT,
+            &["age1first".to_string(), "age1second".to_string()],
+            &["age1new".to_string()],
+        );
+        assert_eq!("# team\nage1new\n\n# me\n", replace);
+    }
+}
@@ -7,20 +7,31 @@ mod no_agent;
 use std::io;
 use std::io::{Read, Write};
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::{Command, Output, Stdio};
 
 use crate::age::identity::get_identity;
 use crate::backend::{exit_status_to_result, output_to_result};
+use anyhow::anyhow;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use std::fs;
 
 pub const RECIPIENT_FILE_NAME: &str = ".age-id";
 
-pub fn encrypt(content: &mut impl Read, recipient: &str, out_path: &Path) -> anyhow::Result<()> {
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+const ARMOR_BEGIN: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
+pub fn encrypt(
+    content: &mut impl Read,
+    recipients: &[impl AsRef<str>],
+    out_path: &Path,
+) -> anyhow::Result<()> {
     let (in_read, mut in_write) = io::pipe()?;
     let mut child = Command::new("age")
         .arg("--encrypt")
         .arg("--armor")
-        .arg("--recipient")
-        .arg(recipient)
+        .args(recipients.iter().flat_map(|r| ["--recipient", r.as_ref()]))
         .arg("--output")
         .arg(out_path.as_os_str())
         .stdin(in_read)
@@ -31,7 +42,107 @@ pub fn encrypt(content: &mut impl Read, recipient: &str, out_path: &Path) -> any
     exit_status_to_result(exit_status, "age")
 }
 
+pub fn encrypt_with_passphrase(content: &mut impl Read, out_path: &Path) -> anyhow::Result<()> {
+    let (in_read, mut in_write) = io::pipe()?;
+    let mut child = Command::new("age")
+        .arg("--encrypt")
+        .arg("--armor")
+        .arg("--passphrase")
+        .arg("--output")
+        .arg(out_path.as_os_str())
+        .stdin(in_read)
+        .spawn()?;
+    io::copy(content, &mut in_write)?;
+    drop(in_write);
+    let exit_status = child.wait()?;
+    exit_status_to_result(exit_status, "age")
+}
+
+pub fn check_recipient(recipient: &str) -> anyhow::Result<()> {
+    // bech32 with the human readable part age, or age1<plugin> for plugin recipients
+    let is_native = recipient
+        .rsplit_once('1')
+        .filter(|(hrp, _)| hrp.starts_with("age"))
+        .is_some_and(|(hrp, data)| {
+            let expected_len = if hrp == "age" {
+                58..=58
+            } else {
+                6..=usize::MAX
+            };
+            expected_len.contains(&data.len()) && data.chars().all(|c| BECH32_CHARSET.contains(c))
+        });
+    let is_ssh = ["ssh-ed25519 ", "ssh-rsa "]
+        .iter()
+        .any(|prefix| recipient.starts_with(prefix));
+    if is_native || is_ssh {
+        Ok(())
+    } else {
+        Err(anyhow!("{recipient} is not an age or ssh recipient"))
+    }
+}
+
+/// The types of the recipient stanzas in the header of a file, e.g. X25519 or ssh-ed25519
+pub fn recipient_stanzas(path: &Path) -> anyhow::Result<Vec<String>> {
+    let content = fs::read(path)?;
+    let binary = if content.starts_with(ARMOR_BEGIN.as_bytes()) {
+        let armored = String::from_utf8(content)?;
+        let base64: String = armored
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with("-----"))
+            .collect();
+        BASE64_STANDARD.decode(base64)?
+    } else {
+        content
+    };
+    let header_len = binary
+        .windows(4)
+        .position(|w| w == b"\n---")
+        .ok_or_else(|| anyhow!("{} has no age header", path.display()))?;
+    let header = std::str::from_utf8(&binary[..header_len])?;
+    Ok(header
+        .lines()
+        .filter_map(|line| line.strip_prefix("-> "))
+        .map(|stanza| stanza.split(' ').next().unwrap_or_default().to_string())
+        // grease stanzas are random noise added by some implementations
+        .filter(|stanza_type| !stanza_type.ends_with("-grease"))
+        .collect())
+}
+
+/// The type of the stanza age writes for the recipient, or None for plugin recipients
+pub fn stanza_type(recipient: &str) -> Option<&'static str> {
+    if recipient.starts_with("ssh-ed25519 ") {
+        Some("ssh-ed25519")
+    } else if recipient.starts_with("ssh-rsa ") {
+        Some("ssh-rsa")
+    } else if recipient.rfind('1') == Some(3) {
+        Some("X25519")
+    } else {
+        None
+    }
+}
+
 pub fn decrypt(path: &Path) -> anyhow::Result<String> {
+    output_to_result(decrypt_with_identity(path)?)
+}
+
+pub fn decrypt_binary(path: &Path) -> anyhow::Result<Vec<u8>> {
+    binary_output_to_result(decrypt_with_identity(path)?)
+}
+
+pub fn decrypt_with_passphrase(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let output = Command::new("age")
+        .arg("--decrypt")
+        .arg(path.as_os_str())
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .output()?;
+    exit_status_to_result(output.status, "age")?;
+    Ok(output.stdout)
+}
+
+fn decrypt_with_identity(path: &Path) -> anyhow::Result<Output> {
     let identity = get_identity()?;
     let (in_read, mut in_write) = io::pipe()?;
     let child = Command::new("age")
@@ -45,6 +156,65 @@ pub fn decrypt(path: &Path) -> anyhow::Result<String> {
         .spawn()?;
     in_write.write_all(identity.as_slice())?;
     drop(in_write);
-    let output = child.wait_with_output()?;
-    output_to_result(output)
+    Ok(child.wait_with_output()?)
+}
+
+fn binary_output_to_result(output: Output) -> anyhow::Result<Vec<u8>> {
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(anyhow!(String::from_utf8(output.stderr)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_recipient() {
+        assert!(
+            check_recipient("age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p")
+                .is_ok()
+        );
+        assert!(check_recipient("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAI me@host").is_ok());
+        assert!(check_recipient("age1yubikey1qwt50d05nh5vutpdzmlg5wn80xq5negm").is_ok());
+        assert!(check_recipient("age1").is_err());
+        assert!(check_recipient("age1xyz").is_err());
+        assert!(check_recipient("AGE-SECRET-KEY-1QQQ").is_err());
+        assert!(check_recipient("test@example.com").is_err());
+    }
+
+    #[test]
+    fn test_recipient_stanzas() -> anyhow::Result<()> {
+        let header = "age-encryption.org/v1\n-> X25519 abc\nbody\n-> x-grease y\nnoise\n-> ssh-ed25519 tag def\nbody\n--- mac\npayload";
+        let dir = tempfile::tempdir()?;
+        let binary = dir.path().join("binary");
+        fs::write(&binary, header)?;
+        let armored = dir.path().join("armored");
+        fs::write(
+            &armored,
+            format!(
+                "{ARMOR_BEGIN}\n{}\n-----END AGE ENCRYPTED FILE-----\n",
+                BASE64_STANDARD.encode(header)
+            ),
+        )?;
+        for path in [binary, armored] {
+            assert_eq!(vec!["X25519", "ssh-ed25519"], recipient_stanzas(&path)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_stanza_type() {
+        assert_eq!(
+            Some("X25519"),
+            stanza_type("age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p")
+        );
+        assert_eq!(Some("ssh-rsa"), stanza_type("ssh-rsa AAAAB3Nza me@host"));
+        assert_eq!(
+            None,
+            stanza_type("age1yubikey1qwt50d05nh5vutpdzmlg5wn80xq5negm")
+        );
+    }
 }
@@ -24,6 +24,14 @@ pub fn get_identity() -> anyhow::Result<Vec<u8>> {
     Ok(id)
 }
 
+/// Replaces the identity cached by a running agent; does nothing if no agent is running
+pub fn refresh_identity(id: &[u8]) -> anyhow::Result<()> {
+    if entrust_agent::client::is_server_running() {
+        set_identity(id)?;
+    }
+    Ok(())
+}
+
 fn start_agent() -> io::Result<()> {
     let seconds = entrust_agent::env::agent_seconds();
     let ent_agent_bin = entrust_agent::env::agent_bin();
@@ -1,15 +1,15 @@
 use crate::age;
 use crate::backend::is_age_encrypted;
 use anyhow::anyhow;
-use std::io::{IsTerminal, Read};
+use std::io::{IsTerminal, Read, Write};
 use std::path::Path;
 use std::process::{Command, Stdio};
 use std::{env, fs, io};
 
 #[cfg(feature = "agent")]
-pub use age::agent::get_identity;
+pub use age::agent::{get_identity, refresh_identity};
 #[cfg(not(feature = "agent"))]
-pub use age::no_agent::get_identity;
+pub use age::no_agent::{get_identity, refresh_identity};
 
 pub fn read_identity() -> anyhow::Result<Vec<u8>> {
     if !io::stdin().is_terminal() {
@@ -21,6 +21,61 @@ pub fn read_identity() -> anyhow::Result<Vec<u8>> {
     }
 }
 
+/// Generates a new identity with age-keygen and returns it together with its recipient
+pub fn generate_identity() -> anyhow::Result<(String, String)> {
+    let output = Command::new("age-keygen")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            String::from_utf8(output.stderr)?.trim().to_string()
+        ));
+    }
+    let identity = String::from_utf8(output.stdout)?;
+    let recipient = match identity
+        .lines()
+        .find_map(|line| line.strip_prefix("# public key: "))
+    {
+        Some(recipient) => recipient.trim().to_string(),
+        None => recipients_of(identity.as_bytes())?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("age-keygen did not generate an identity"))?,
+    };
+    Ok((identity, recipient))
+}
+
+/// The recipients of the identities, as printed by age-keygen -y
+pub fn recipients_of(identity: &[u8]) -> anyhow::Result<Vec<String>> {
+    let (in_read, mut in_write) = io::pipe()?;
+    let child = Command::new("age-keygen")
+        .arg("-y")
+        .stdin(in_read)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    in_write.write_all(identity)?;
+    drop(in_write);
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .map(str::to_string)
+            .collect())
+    } else {
+        Err(anyhow!(
+            String::from_utf8(output.stderr)?.trim().to_string()
+        ))
+    }
+}
+
+/// The path of the identity file given in AGE_IDENTITY
+pub fn identity_file() -> Option<String> {
+    env::var("AGE_IDENTITY").ok()
+}
+
 fn read_identity_from_stdin() -> anyhow::Result<Vec<u8>> {
     let mut identity = Vec::new();
     io::stdin().read_to_end(&mut identity)?;
@@ -49,7 +104,3 @@ fn decrypt_identity_file(identity_file: String) -> anyhow::Result<Vec<u8>> {
         Err(anyhow!("age exited with an error"))
     }
 }
-
-fn identity_file() -> Option<String> {
-    env::var("AGE_IDENTITY").ok()
-}
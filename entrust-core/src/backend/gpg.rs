@@ -1,4 +1,5 @@
 use crate::backend::{exit_status_to_result, output_to_result};
+use anyhow::anyhow;
 use std::io;
 use std::io::Read;
 use std::path::Path;
@@ -6,14 +7,17 @@ use std::process::{Command, Stdio};
 
 pub const RECIPIENT_FILE_NAME: &str = ".gpg-id";
 
-pub fn encrypt(content: &mut impl Read, recipient: &str, out_path: &Path) -> anyhow::Result<()> {
+pub fn encrypt(
+    content: &mut impl Read,
+    recipients: &[impl AsRef<str>],
+    out_path: &Path,
+) -> anyhow::Result<()> {
     let (in_read, mut in_write) = io::pipe()?;
     let child = Command::new("gpg")
         .arg("--encrypt")
         .arg("--armor")
         .arg("--quiet")
-        .arg("--recipient")
-        .arg(recipient)
+        .args(recipients.iter().flat_map(|r| ["--recipient", r.as_ref()]))
         .arg("--output")
         .arg(out_path.as_os_str())
         .stdin(in_read)
@@ -24,6 +28,60 @@ pub fn encrypt(content: &mut impl Read, recipient: &str, out_path: &Path) -> any
     exit_status_to_result(exit_status, "gpg")
 }
 
+pub fn check_recipient(recipient: &str) -> anyhow::Result<()> {
+    let status = Command::new("gpg")
+        .arg("--list-keys")
+        .arg("--with-colons")
+        .arg("--")
+        .arg(recipient)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("gpg has no public key for {recipient}"))
+    }
+}
+
+/// The key ids a file is encrypted to, read without decrypting it
+pub fn encrypted_to(path: &Path) -> anyhow::Result<Vec<String>> {
+    let output = Command::new("gpg")
+        .arg("--batch")
+        .arg("--list-only")
+        .arg("--list-packets")
+        .arg(path.as_os_str())
+        .stdin(Stdio::null())
+        .output()?;
+    let packets = output_to_result(output)?;
+    Ok(packets
+        .lines()
+        .filter(|line| line.starts_with(":pubkey enc packet:"))
+        .filter_map(|line| line.split("keyid ").nth(1))
+        .map(|id| id.trim().to_string())
+        .collect())
+}
+
+/// The key ids of the primary key and subkeys of the recipient
+pub fn key_ids(recipient: &str) -> anyhow::Result<Vec<String>> {
+    let output = Command::new("gpg")
+        .arg("--batch")
+        .arg("--list-keys")
+        .arg("--with-colons")
+        .arg("--")
+        .arg(recipient)
+        .stdin(Stdio::null())
+        .output()?;
+    let keys = output_to_result(output)?;
+    Ok(keys
+        .lines()
+        .filter(|line| line.starts_with("pub:") || line.starts_with("sub:"))
+        .filter_map(|line| line.split(':').nth(4))
+        .map(str::to_string)
+        .collect())
+}
+
 pub fn decrypt(path: &Path) -> anyhow::Result<String> {
     let output = Command::new("gpg")
         .arg("--decrypt")
@@ -0,0 +1,218 @@
+use anyhow::anyhow;
+use sha1::{Digest, Sha1};
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const INDEX_MAGIC: &[u8; 8] = b"ENTHIBP1";
+const HASH_LEN: usize = 20;
+const RECORD_LEN: u64 = HASH_LEN as u64 + 4;
+
+/// A local copy of the Pwned Passwords SHA-1 dataset, either as the downloaded text file
+/// sorted by hash (`HASH:COUNT` per line) or as a compact index created by [`build_index`]
+pub struct BreachDatabase {
+    reader: BufReader<File>,
+    len: u64,
+    is_index: bool,
+}
+
+impl BreachDatabase {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut magic = [0u8; INDEX_MAGIC.len()];
+        let is_index = file.read_exact(&mut magic).is_ok() && &magic == INDEX_MAGIC;
+        Ok(BreachDatabase {
+            reader: BufReader::new(file),
+            len,
+            is_index,
+        })
+    }
+
+    /// Returns how often the password appears in the dataset, if at all
+    pub fn lookup(&mut self, password: &str) -> anyhow::Result<Option<u64>> {
+        let hash: [u8; HASH_LEN] = Sha1::digest(password.as_bytes()).into();
+        if self.is_index {
+            self.lookup_index(&hash)
+        } else {
+            self.lookup_text(&to_hex(&hash))
+        }
+    }
+
+    fn lookup_index(&mut self, hash: &[u8; HASH_LEN]) -> anyhow::Result<Option<u64>> {
+        let records = (self.len - INDEX_MAGIC.len() as u64) / RECORD_LEN;
+        let (mut low, mut high) = (0, records);
+        let mut record = [0u8; RECORD_LEN as usize];
+        while low < high {
+            let mid = (low + high) / 2;
+            self.reader
+                .seek(SeekFrom::Start(INDEX_MAGIC.len() as u64 + mid * RECORD_LEN))?;
+            self.reader.read_exact(&mut record)?;
+            match record[..HASH_LEN].cmp(hash) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => {
+                    let count = u32::from_be_bytes(record[HASH_LEN..].try_into()?);
+                    return Ok(Some(count as u64));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn lookup_text(&mut self, hash: &str) -> anyhow::Result<Option<u64>> {
+        // low is always the start of a line, high the start of a line or the end of the file
+        let (mut low, mut high) = (0, self.len);
+        let mut line = Vec::with_capacity(64);
+        while low < high {
+            let mid = (low + high) / 2;
+            let mut start = if mid == low {
+                low
+            } else {
+                self.reader.seek(SeekFrom::Start(mid - 1))?;
+                line.clear();
+                mid - 1 + self.reader.read_until(b'\n', &mut line)? as u64
+            };
+            if start >= high {
+                start = low;
+            }
+            self.reader.seek(SeekFrom::Start(start))?;
+            line.clear();
+            let line_len = self.reader.read_until(b'\n', &mut line)? as u64;
+            let (line_hash, count) = parse_line(&line)?;
+            match line_hash.to_ascii_uppercase().as_str().cmp(hash) {
+                Ordering::Less => low = start + line_len,
+                Ordering::Greater => high = start,
+                Ordering::Equal => return Ok(Some(count)),
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Converts the text dataset into a compact index with fixed-size records;
+/// returns the number of records
+pub fn build_index(text: &Path, out: &Path) -> anyhow::Result<u64> {
+    let reader = BufReader::new(File::open(text)?);
+    let mut writer = BufWriter::new(File::create(out)?);
+    writer.write_all(INDEX_MAGIC)?;
+    let mut previous = [0u8; HASH_LEN];
+    let mut records = 0;
+    for line in reader.split(b'\n') {
+        let line = line?;
+        if line.iter().all(|b| b.is_ascii_whitespace()) {
+            continue;
+        }
+        let (hash, count) = parse_line(&line)?;
+        let hash = from_hex(&hash)?;
+        if records > 0 && hash <= previous {
+            return Err(anyhow!("The hashes are not sorted in ascending order"));
+        }
+        writer.write_all(&hash)?;
+        writer.write_all(&u32::try_from(count).unwrap_or(u32::MAX).to_be_bytes())?;
+        previous = hash;
+        records += 1;
+    }
+    writer.flush()?;
+    Ok(records)
+}
+
+fn parse_line(line: &[u8]) -> anyhow::Result<(String, u64)> {
+    let line = std::str::from_utf8(line)?.trim();
+    let (hash, count) = line.split_once(':').unwrap_or((line, "1"));
+    if hash.len() != 2 * HASH_LEN || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(anyhow!("Invalid line in breach database: {line}"));
+    }
+    Ok((hash.to_string(), count.parse()?))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+fn from_hex(hex: &str) -> anyhow::Result<[u8; HASH_LEN]> {
+    let mut bytes = [0u8; HASH_LEN];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const PASSWORDS: [(&str, u64); 5] = [
+        ("password", 52256179),
+        ("123456", 209972844),
+        ("hunter2", 30469),
+        ("letmein", 1),
+        ("correct horse battery staple", 384),
+    ];
+
+    fn setup() -> anyhow::Result<TempDir> {
+        let dir = tempfile::tempdir()?;
+        let mut lines: Vec<_> = PASSWORDS
+            .iter()
+            .map(|(p, c)| format!("{}:{c}", to_hex(&Sha1::digest(p.as_bytes()))))
+            .collect();
+        lines.sort();
+        fs::write(dir.path().join("pwned.txt"), lines.join("\r\n"))?;
+        Ok(dir)
+    }
+
+    fn assert_lookups(database: &mut BreachDatabase) -> anyhow::Result<()> {
+        for (password, count) in PASSWORDS {
+            assert_eq!(Some(count), database.lookup(password)?, "{password}");
+        }
+        for password in ["", "hunter3", "x7#Lq!9vR@2mZp$W"] {
+            assert_eq!(None, database.lookup(password)?, "{password}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_text() -> anyhow::Result<()> {
+        let dir = setup()?;
+        let mut database = BreachDatabase::open(&dir.path().join("pwned.txt"))?;
+        assert_lookups(&mut database)
+    }
+
+    #[test]
+    fn test_lookup_index() -> anyhow::Result<()> {
+        let dir = setup()?;
+        let index = dir.path().join("pwned.idx");
+        let records = build_index(&dir.path().join("pwned.txt"), &index)?;
+        assert_eq!(PASSWORDS.len() as u64, records);
+        let mut database = BreachDatabase::open(&index)?;
+        assert!(database.is_index);
+        assert_lookups(&mut database)
+    }
+
+    #[test]
+    fn test_unsorted() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let text = dir.path().join("pwned.txt");
+        fs::write(
+            &text,
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF:1\n0000000000000000000000000000000000000000:1",
+        )?;
+        assert!(build_index(&text, &dir.path().join("pwned.idx")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_hash() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let text = dir.path().join("pwned.txt");
+        fs::write(&text, "00000000000000000000000000000000000000ä:1")?;
+        assert!(build_index(&text, &dir.path().join("pwned.idx")).is_err());
+        fs::write(&text, "000000000000000000000000000000000000000G:1")?;
+        assert!(build_index(&text, &dir.path().join("pwned.idx")).is_err());
+        assert!(BreachDatabase::open(&text)?.lookup("password").is_err());
+        Ok(())
+    }
+}
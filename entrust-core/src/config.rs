@@ -0,0 +1,225 @@
+use crate::{GeneratorProfile, ResolveStrategy, Template};
+use anyhow::{Context, anyhow};
+use glob::{MatchOptions, Pattern};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// The name of the optional configuration file in the root of the store
+pub const CONFIG_FILE: &str = ".ent.toml";
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StoreConfig {
+    pub generate: GenerateConfig,
+    pub resolve: ResolveConfig,
+    /// Templates for new entries, in addition to and overriding the builtin ones
+    pub templates: BTreeMap<String, Template>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResolveConfig {
+    /// The strategies tried in order if a key does not exist exactly; only unique prefixes by default
+    pub strategies: Vec<ResolveStrategy>,
+}
+
+impl Default for ResolveConfig {
+    fn default() -> Self {
+        ResolveConfig {
+            strategies: vec![ResolveStrategy::Prefix],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenerateConfig {
+    /// The name of a builtin wordlist or the path to a wordlist file, relative to the store
+    pub wordlist: Option<String>,
+    pub profiles: BTreeMap<String, GeneratorProfile>,
+    /// Glob patterns matching keys to the names of profiles, tried in order
+    pub mapping: Vec<ProfileMapping>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileMapping {
+    pub glob: String,
+    pub profile: String,
+}
+
+impl StoreConfig {
+    /// Reads the configuration of the given store, or the default if the store has none
+    pub fn load(store: &Path) -> anyhow::Result<StoreConfig> {
+        let path = store.join(CONFIG_FILE);
+        match fs::read_to_string(&path) {
+            Ok(content) => StoreConfig::parse(&content)
+                .with_context(|| format!("Invalid configuration in {}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(StoreConfig::default()),
+            Err(e) => Err(e).with_context(|| format!("Could not read {}", path.display())),
+        }
+    }
+
+    pub fn parse(content: &str) -> anyhow::Result<StoreConfig> {
+        let config: StoreConfig = toml::from_str(content)?;
+        for template in config.templates.values() {
+            if let Some(profile) = &template.profile {
+                config.profile(profile)?;
+            }
+        }
+        for mapping in &config.generate.mapping {
+            Pattern::new(&mapping.glob)
+                .with_context(|| format!("Invalid glob pattern {}", mapping.glob))?;
+            config.profile(&mapping.profile)?;
+        }
+        Ok(config)
+    }
+
+    /// The profile with the given name, with the store's default options applied
+    pub fn profile(&self, name: &str) -> anyhow::Result<GeneratorProfile> {
+        self.generate
+            .profiles
+            .get(name)
+            .map(|profile| self.with_defaults(profile.clone()))
+            .ok_or_else(|| anyhow!("The generator profile {name} does not exist"))
+    }
+
+    /// The profile of the first mapping whose glob matches the key,
+    /// or the store's default options if none matches
+    pub fn profile_for_key(&self, key: &str) -> anyhow::Result<GeneratorProfile> {
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        let key = key.trim_matches('/');
+        let mapping = self.generate.mapping.iter().find(|mapping| {
+            Pattern::new(&mapping.glob).is_ok_and(|glob| glob.matches_with(key, options))
+        });
+        match mapping {
+            Some(mapping) => self.profile(&mapping.profile),
+            None => Ok(self.default_profile()),
+        }
+    }
+
+    /// The template with the given name, defined in the store or else builtin
+    pub fn template(&self, name: &str) -> anyhow::Result<Template> {
+        self.templates
+            .get(name)
+            .cloned()
+            .or_else(|| Template::builtin(name))
+            .ok_or_else(|| anyhow!("The template {name} does not exist"))
+    }
+
+    /// The store's default options, which apply to keys without a matching profile
+    pub fn default_profile(&self) -> GeneratorProfile {
+        self.with_defaults(GeneratorProfile::default())
+    }
+
+    /// Applies the store's default options, which are used by profiles of any type
+    fn with_defaults(&self, profile: GeneratorProfile) -> GeneratorProfile {
+        GeneratorProfile {
+            wordlist: profile.wordlist.or_else(|| self.generate.wordlist.clone()),
+            ..profile
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GeneratorType, PasswordSource};
+
+    #[test]
+    fn test_parse() -> anyhow::Result<()> {
+        assert_eq!(StoreConfig::default(), StoreConfig::parse("")?);
+        let config = StoreConfig::parse("[generate]\nwordlist = \"eff-short\"\n")?;
+        assert_eq!(Some("eff-short"), config.generate.wordlist.as_deref());
+        assert!(StoreConfig::parse("[generate]\nwordlsit = \"eff-short\"\n").is_err());
+        assert_eq!(
+            vec![ResolveStrategy::Prefix],
+            StoreConfig::default().resolve.strategies
+        );
+        let config = StoreConfig::parse("[resolve]\nstrategies = [\"prefix\", \"substring\"]\n")?;
+        assert_eq!(
+            vec![ResolveStrategy::Prefix, ResolveStrategy::Substring],
+            config.resolve.strategies
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_profiles() -> anyhow::Result<()> {
+        let config = StoreConfig::parse(
+            r#"
+[generate]
+wordlist = "eff-short"
+
+[generate.profiles.bank]
+type = "word"
+length = 16
+no-symbols = true
+
+[generate.profiles.pin]
+type = "pin"
+
+[[generate.mapping]]
+glob = "bank/*/pin"
+profile = "pin"
+
+[[generate.mapping]]
+glob = "bank/**"
+profile = "bank"
+"#,
+        )?;
+        let bank = config.profile_for_key("bank/foo/password")?;
+        assert_eq!(Some(GeneratorType::Word), bank.r#type);
+        assert_eq!(Some(16), bank.length);
+        assert_eq!(Some(true), bank.no_symbols);
+        assert_eq!(Some("eff-short"), bank.wordlist.as_deref());
+        let pin = config.profile_for_key("bank/foo/pin")?;
+        assert_eq!(Some(GeneratorType::Pin), pin.r#type);
+        assert_eq!(None, config.profile_for_key("bank")?.r#type);
+        assert_eq!(None, config.profile_for_key("web/bank/pin")?.r#type);
+        assert!(config.profile("unknown").is_err());
+
+        let unknown = "[[generate.mapping]]\nglob = \"*\"\nprofile = \"unknown\"\n";
+        assert!(StoreConfig::parse(unknown).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_templates() -> anyhow::Result<()> {
+        let config = StoreConfig::parse(
+            r#"
+[templates.login]
+fields = ["email"]
+password = "generate"
+
+[templates.wifi]
+fields = ["ssid"]
+"#,
+        )?;
+        let login = config.template("login")?;
+        assert_eq!(vec!["email"], login.fields);
+        assert_eq!(PasswordSource::Generate, login.password);
+        assert!(!login.notes);
+        assert_eq!(PasswordSource::Offer, config.template("wifi")?.password);
+        assert_eq!(
+            Template::builtin("server"),
+            Some(config.template("server")?)
+        );
+        assert!(config.template("unknown").is_err());
+        assert!(StoreConfig::parse("[templates.x]\nprofile = \"unknown\"\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_missing() -> anyhow::Result<()> {
+        let store = tempfile::tempdir()?;
+        assert_eq!(StoreConfig::default(), StoreConfig::load(store.path())?);
+        Ok(())
+    }
+}
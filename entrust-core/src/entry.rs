@@ -0,0 +1,114 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Entry {
+    pub password: String,
+    pub fields: Vec<(String, String)>,
+    pub notes: Vec<String>,
+}
+
+impl Entry {
+    pub fn parse(content: &str) -> Entry {
+        let mut lines = content.lines();
+        let mut entry = Entry {
+            password: lines.next().unwrap_or_default().to_string(),
+            ..Default::default()
+        };
+        for line in lines {
+            match parse_field(line) {
+                Some((name, value)) => entry.fields.push((name.to_string(), value.to_string())),
+                None => entry.notes.push(line.to_string()),
+            }
+        }
+        entry
+    }
+
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Replaces the first line of the content, which holds the password, and keeps all other lines as they are
+pub fn replace_password(content: &str, password: &str) -> String {
+    match content.split_once('\n') {
+        Some((_, rest)) => format!("{password}\n{rest}"),
+        None => password.to_string(),
+    }
+}
+
+fn parse_field(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = match line.split_once(": ") {
+        Some(split) => split,
+        None => (line.strip_suffix(':')?, ""),
+    };
+    if name.is_empty() || name.trim() != name {
+        None
+    } else {
+        Some((name, value))
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.password)?;
+        for (name, value) in &self.fields {
+            if value.is_empty() {
+                write!(f, "\n{name}:")?;
+            } else {
+                write!(f, "\n{name}: {value}")?;
+            }
+        }
+        for line in &self.notes {
+            write!(f, "\n{line}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let entry = Entry::parse("secret\nuser: me\nsome note\nurl: https://example.com\n");
+        assert_eq!("secret", entry.password);
+        assert_eq!(
+            vec![
+                ("user".to_string(), "me".to_string()),
+                ("url".to_string(), "https://example.com".to_string())
+            ],
+            entry.fields
+        );
+        assert_eq!(vec!["some note"], entry.notes);
+        assert_eq!(Some("me"), entry.field("User"));
+        assert_eq!(None, entry.field("email"));
+    }
+
+    #[test]
+    fn test_no_field() {
+        let entry = Entry::parse("secret\nhttps://example.com\n  indented: no\n: empty");
+        assert!(entry.fields.is_empty());
+        assert_eq!(3, entry.notes.len());
+    }
+
+    #[test]
+    fn test_display() {
+        let content = "secret\nuser: me\nempty:\nsome note";
+        assert_eq!(content, Entry::parse(content).to_string());
+        assert_eq!("", Entry::parse("").to_string());
+    }
+
+    #[test]
+    fn test_replace_password() {
+        assert_eq!("new", replace_password("old", "new"));
+        assert_eq!("new\n", replace_password("old\n", "new"));
+        assert_eq!(
+            "new\nuser:  me \n\nnote\n",
+            replace_password("old\nuser:  me \n\nnote\n", "new")
+        );
+    }
+}
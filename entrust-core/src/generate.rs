@@ -1,39 +1,550 @@
-use rand::prelude::IteratorRandom;
-
-const WORDLIST: &str = include_str!("../eff_large.wordlist");
+use crate::Wordlist;
+use anyhow::anyhow;
+use itertools::Itertools;
+use rand::prelude::{IndexedMutRandom, IndexedRandom, IteratorRandom, SliceRandom};
+use serde::Deserialize;
 
 const PRINTABLE_ASCII: &str = r#"!"$#%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\]^_`abcdefghijklmnopqrstuvwxyz{|}~"#;
 
+pub const AMBIGUOUS_CHARS: &str = "0Oo1lI|";
+
 pub fn generate_passphrase(len: u8, separator: &str) -> String {
-    let phrase_iterable = (0..len).map(|_| random_word());
-    itertools::intersperse(phrase_iterable, separator).collect()
+    Wordlist::default().generate_passphrase(len, separator)
 }
 
 pub fn generate_password(len: u8) -> String {
-    (0..len).map(|_| random_ascii()).collect()
+    GeneratorPolicy::default()
+        .generate(len)
+        .expect("the default policy has no requirements")
+}
+
+/// Generates a random PIN which is not a trivial sequence like 0000, 1234 or 1212
+pub fn generate_pin(len: u8) -> String {
+    let policy = GeneratorPolicy::empty().with_class(CharClass::Digits, 0);
+    loop {
+        let pin = policy
+            .generate(len)
+            .expect("the digit policy has no requirements");
+        if !is_trivial_pin(&pin) {
+            return pin;
+        }
+    }
+}
+
+pub fn pin_entropy(len: u8) -> f64 {
+    len as f64 * 10f64.log2()
 }
 
-fn random_word() -> &'static str {
-    WORDLIST.lines().choose(&mut rand::rng()).unwrap()
+/// Whether the PIN consists of one repeated digit, ascending or descending consecutive digits,
+/// or a repeated shorter pattern
+pub fn is_trivial_pin(pin: &str) -> bool {
+    let digits: Vec<i32> = pin
+        .chars()
+        .filter_map(|c| c.to_digit(10).map(|d| d as i32))
+        .collect();
+    let len = digits.len();
+    let sequence = |step: i32| digits.windows(2).all(|w| w[1] - w[0] == step);
+    let repeated = (1..len)
+        .filter(|&period| len.is_multiple_of(period))
+        .any(|period| digits.chunks(period).all_equal());
+    (len >= 2 && repeated) || (len >= 3 && (sequence(1) || sequence(-1)))
+}
+
+const CONSONANTS: &str = "bcdfghjklmnprstvwz";
+const VOWELS: &str = "aeiou";
+
+/// Generates a pronounceable password made of alternating consonants and vowels
+pub fn generate_pronounceable(len: u8) -> String {
+    let mut rng = rand::rng();
+    (0..len)
+        .map(|i| {
+            let chars = if i % 2 == 0 { CONSONANTS } else { VOWELS };
+            chars.chars().choose(&mut rng).unwrap()
+        })
+        .collect()
+}
+
+pub fn pronounceable_entropy(len: u8) -> f64 {
+    let consonants = len.div_ceil(2) as f64 * (CONSONANTS.len() as f64).log2();
+    let vowels = (len / 2) as f64 * (VOWELS.len() as f64).log2();
+    consonants + vowels
 }
 
 pub fn random_ascii() -> char {
     PRINTABLE_ASCII.chars().choose(&mut rand::rng()).unwrap()
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digits,
+    Symbols,
+}
+
+impl CharClass {
+    pub const ALL: [CharClass; 4] = [
+        CharClass::Lower,
+        CharClass::Upper,
+        CharClass::Digits,
+        CharClass::Symbols,
+    ];
+
+    pub fn chars(&self) -> &'static str {
+        match self {
+            CharClass::Lower => "abcdefghijklmnopqrstuvwxyz",
+            CharClass::Upper => "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+            CharClass::Digits => "0123456789",
+            CharClass::Symbols => r#"!"$#%&'()*+,-./:;<=>?@[\]^_`{|}~"#,
+        }
+    }
+}
+
+/// Describes the characters a generated password is made of
+/// and how many characters of each set it must contain at least
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratorPolicy {
+    sets: Vec<CharSet>,
+    excluded: Vec<char>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct CharSet {
+    class: Option<CharClass>,
+    chars: Vec<char>,
+    min: u8,
+}
+
+impl Default for GeneratorPolicy {
+    fn default() -> Self {
+        CharClass::ALL
+            .into_iter()
+            .fold(GeneratorPolicy::empty(), |policy, class| {
+                policy.with_class(class, 0)
+            })
+    }
+}
+
+impl GeneratorPolicy {
+    pub fn empty() -> Self {
+        GeneratorPolicy {
+            sets: Vec::new(),
+            excluded: Vec::new(),
+        }
+    }
+
+    /// Lowercase hexadecimal digits
+    pub fn hex() -> Self {
+        GeneratorPolicy::empty().with_custom("0123456789abcdef", 0)
+    }
+
+    /// The characters of URL-safe base64
+    pub fn base64() -> Self {
+        GeneratorPolicy::empty()
+            .with_class(CharClass::Upper, 0)
+            .with_class(CharClass::Lower, 0)
+            .with_class(CharClass::Digits, 0)
+            .with_custom("-_", 0)
+    }
+
+    pub fn with_class(mut self, class: CharClass, min: u8) -> Self {
+        match self.sets.iter_mut().find(|s| s.class == Some(class)) {
+            Some(set) => set.min = min,
+            None => self.sets.push(CharSet {
+                class: Some(class),
+                chars: class.chars().chars().collect(),
+                min,
+            }),
+        }
+        self
+    }
+
+    pub fn without_class(mut self, class: CharClass) -> Self {
+        self.sets.retain(|s| s.class != Some(class));
+        self
+    }
+
+    pub fn with_custom(mut self, chars: &str, min: u8) -> Self {
+        self.sets.push(CharSet {
+            class: None,
+            chars: chars.chars().collect(),
+            min,
+        });
+        self
+    }
+
+    pub fn excluding(mut self, chars: &str) -> Self {
+        self.excluded.extend(chars.chars());
+        self
+    }
+
+    pub fn excluding_ambiguous(self) -> Self {
+        self.excluding(AMBIGUOUS_CHARS)
+    }
+
+    /// All characters a password generated with this policy may contain
+    pub fn alphabet(&self) -> Vec<char> {
+        let mut alphabet: Vec<char> = self.sets.iter().flat_map(|s| self.allowed(s)).collect();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        alphabet
+    }
+
+    pub fn generate(&self, len: u8) -> anyhow::Result<String> {
+        let alphabet = self.alphabet();
+        if alphabet.is_empty() {
+            return Err(anyhow!("The policy does not allow any characters"));
+        }
+        let required: usize = self.sets.iter().map(|s| s.min as usize).sum();
+        if required > len as usize {
+            return Err(anyhow!(
+                "The policy requires {required} characters, but the length is {len}"
+            ));
+        }
+        let mut rng = rand::rng();
+        let mut password = Vec::with_capacity(len as usize);
+        for set in self.sets.iter().filter(|s| s.min > 0) {
+            let allowed: Vec<_> = self.allowed(set).collect();
+            if allowed.is_empty() {
+                return Err(anyhow!(
+                    "A required character set has no allowed characters"
+                ));
+            }
+            for _ in 0..set.min {
+                password.push(*allowed.choose(&mut rng).unwrap());
+            }
+        }
+        while password.len() < len as usize {
+            password.push(*alphabet.choose(&mut rng).unwrap());
+        }
+        password.shuffle(&mut rng);
+        Ok(password.into_iter().collect())
+    }
+
+    /// The entropy of a password of the given length in bits,
+    /// assuming the required characters are only drawn from their sets
+    pub fn entropy(&self, len: u8) -> f64 {
+        let bits = |count: usize| (count.max(1) as f64).log2();
+        let required = self.sets.iter().filter(|s| s.min > 0);
+        let required_bits: f64 = required
+            .clone()
+            .map(|s| s.min as f64 * bits(self.allowed(s).count()))
+            .sum();
+        let required_len: usize = required.map(|s| s.min as usize).sum();
+        let free_len = (len as usize).saturating_sub(required_len);
+        required_bits + free_len as f64 * bits(self.alphabet().len())
+    }
+
+    fn allowed<'a>(&'a self, set: &'a CharSet) -> impl Iterator<Item = char> + 'a {
+        set.chars
+            .iter()
+            .copied()
+            .filter(|c| !self.excluded.contains(c))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Capitalization {
+    #[default]
+    None,
+    Each,
+    One,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Placement {
+    /// Insert characters before or after a word
+    #[default]
+    Between,
+    /// Insert characters inside a word
+    Within,
+}
+
+/// Describes how the words of a generated passphrase are modified and joined
+#[derive(Clone, Debug, PartialEq)]
+pub struct PassphraseOptions {
+    separator: String,
+    capitalization: Capitalization,
+    digits: u8,
+    symbols: u8,
+    placement: Placement,
+    max_len: Option<usize>,
+}
+
+impl Default for PassphraseOptions {
+    fn default() -> Self {
+        PassphraseOptions::new(" ")
+    }
+}
+
+impl PassphraseOptions {
+    pub fn new(separator: &str) -> Self {
+        PassphraseOptions {
+            separator: separator.to_string(),
+            capitalization: Capitalization::None,
+            digits: 0,
+            symbols: 0,
+            placement: Placement::Between,
+            max_len: None,
+        }
+    }
+
+    pub fn capitalized(mut self, capitalization: Capitalization) -> Self {
+        self.capitalization = capitalization;
+        self
+    }
+
+    pub fn with_digits(mut self, digits: u8) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    pub fn with_symbols(mut self, symbols: u8) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn placed(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Limits the number of characters of the passphrase
+    /// by only choosing words which are short enough for every combination to fit
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    pub fn generate(&self, wordlist: &Wordlist, len: u8) -> anyhow::Result<String> {
+        let words = self.candidates(wordlist, len)?;
+        let mut rng = rand::rng();
+        let mut phrase: Vec<Vec<char>> = (0..len)
+            .map(|_| words.choose(&mut rng).unwrap().chars().collect())
+            .collect();
+        match self.capitalization {
+            Capitalization::None => {}
+            Capitalization::Each => phrase.iter_mut().for_each(capitalize),
+            Capitalization::One => {
+                if let Some(word) = phrase.choose_mut(&mut rng) {
+                    capitalize(word);
+                }
+            }
+        }
+        let insertions = (0..self.digits)
+            .map(|_| CharClass::Digits)
+            .chain((0..self.symbols).map(|_| CharClass::Symbols));
+        for class in insertions {
+            let c = class.chars().chars().choose(&mut rng).unwrap();
+            let Some(word) = phrase.choose_mut(&mut rng) else {
+                break;
+            };
+            let index = match self.placement {
+                Placement::Between => [0, word.len()].choose(&mut rng).copied().unwrap(),
+                Placement::Within if word.len() < 2 => word.len(),
+                Placement::Within => (1..word.len()).choose(&mut rng).unwrap(),
+            };
+            word.insert(index, c);
+        }
+        Ok(phrase
+            .into_iter()
+            .map(|w| w.into_iter().collect::<String>())
+            .join(&self.separator))
+    }
+
+    /// The entropy of a passphrase with the given number of words in bits;
+    /// the positions of inserted characters are not counted
+    pub fn entropy(&self, wordlist: &Wordlist, len: u8) -> f64 {
+        let Ok(words) = self.candidates(wordlist, len) else {
+            return 0.0;
+        };
+        let bits = |count: usize| (count.max(1) as f64).log2();
+        let mut entropy = len as f64 * bits(words.len());
+        if self.capitalization == Capitalization::One {
+            entropy += bits(len as usize);
+        }
+        entropy += self.digits as f64 * bits(CharClass::Digits.chars().len());
+        entropy += self.symbols as f64 * bits(CharClass::Symbols.chars().len());
+        entropy
+    }
+
+    fn candidates<'a>(&self, wordlist: &'a Wordlist, len: u8) -> anyhow::Result<Vec<&'a str>> {
+        let Some(max_len) = self.max_len else {
+            return Ok(wordlist.words().collect());
+        };
+        let fixed = self.separator.chars().count() * (len as usize).saturating_sub(1)
+            + self.digits as usize
+            + self.symbols as usize;
+        let per_word = max_len.saturating_sub(fixed) / (len.max(1) as usize);
+        let words: Vec<_> = wordlist
+            .words()
+            .filter(|w| w.chars().count() <= per_word)
+            .collect();
+        if words.is_empty() {
+            return Err(anyhow!(
+                "No passphrase of {len} words fits into {max_len} characters"
+            ));
+        }
+        Ok(words)
+    }
+}
+
+fn capitalize(word: &mut Vec<char>) {
+    if let Some(first) = word.first().copied() {
+        word.splice(0..1, first.to_uppercase());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use itertools::Itertools;
 
     #[test]
-    fn test_wordlist() {
-        let unique: Vec<_> = WORDLIST.lines().unique().collect();
-        let len = unique.len();
+    fn test_default_policy() {
+        let alphabet: String = GeneratorPolicy::default().alphabet().into_iter().collect();
+        let mut printable: Vec<_> = PRINTABLE_ASCII.chars().collect();
+        printable.sort_unstable();
+        assert_eq!(printable.into_iter().collect::<String>(), alphabet);
+        assert_eq!(20, generate_password(20).chars().count());
+    }
+
+    #[test]
+    fn test_policy_requirements() -> anyhow::Result<()> {
+        let policy = GeneratorPolicy::empty()
+            .with_class(CharClass::Lower, 2)
+            .with_class(CharClass::Digits, 3)
+            .with_custom("-_", 1)
+            .excluding_ambiguous();
+        for _ in 0..100 {
+            let password = policy.generate(8)?;
+            assert_eq!(8, password.len());
+            assert!(password.chars().filter(char::is_ascii_lowercase).count() >= 2);
+            assert!(password.chars().filter(char::is_ascii_digit).count() >= 3);
+            assert!(password.contains(['-', '_']));
+            assert!(!password.contains(|c| AMBIGUOUS_CHARS.contains(c)));
+            assert!(!password.contains(|c: char| c.is_ascii_uppercase()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_entropy() {
+        let lower = GeneratorPolicy::empty()
+            .with_class(CharClass::Lower, 0)
+            .with_custom("012345", 0);
+        assert_eq!(70.0, lower.entropy(14));
+        let digits = GeneratorPolicy::empty().with_custom("01234567", 2);
+        assert_eq!(30.0, digits.entropy(10));
+        let mixed = GeneratorPolicy::empty()
+            .with_class(CharClass::Digits, 2)
+            .with_custom("abcdef", 0);
+        assert_eq!(2.0 * 10f64.log2() + 6.0 * 16f64.log2(), mixed.entropy(8));
+    }
+
+    #[test]
+    fn test_unsatisfiable_policy() {
+        let too_short = GeneratorPolicy::default().with_class(CharClass::Digits, 5);
+        assert!(too_short.generate(4).is_err());
+        assert!(GeneratorPolicy::empty().generate(4).is_err());
+        let all_excluded = GeneratorPolicy::default()
+            .with_custom("01", 1)
+            .excluding("01");
+        assert!(all_excluded.generate(4).is_err());
+        let without_digits = GeneratorPolicy::default()
+            .with_class(CharClass::Digits, 1)
+            .without_class(CharClass::Digits);
+        assert!(!without_digits.alphabet().contains(&'1'));
+        assert!(without_digits.generate(4).is_ok());
+    }
+
+    #[test]
+    fn test_passphrase_options() -> anyhow::Result<()> {
+        let wordlist = Wordlist::default();
+        let options = PassphraseOptions::new(" ")
+            .capitalized(Capitalization::Each)
+            .with_digits(2)
+            .with_symbols(1)
+            .placed(Placement::Within)
+            .with_max_len(40);
+        for _ in 0..100 {
+            let phrase = options.generate(&wordlist, 4)?;
+            assert!(phrase.chars().count() <= 40, "{phrase}");
+            assert_eq!(2, phrase.chars().filter(char::is_ascii_digit).count());
+            let words: Vec<_> = phrase.split(' ').collect();
+            assert_eq!(4, words.len());
+            assert!(
+                words
+                    .iter()
+                    .all(|w| w.starts_with(|c: char| !c.is_lowercase()))
+            );
+        }
+        let one = PassphraseOptions::new(" ").capitalized(Capitalization::One);
+        for _ in 0..100 {
+            let phrase = one.generate(&wordlist, 5)?;
+            assert_eq!(1, phrase.chars().filter(char::is_ascii_uppercase).count());
+        }
+        let impossible = PassphraseOptions::new(" ").with_max_len(10);
+        assert!(impossible.generate(&wordlist, 5).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_passphrase_entropy() {
+        let wordlist = Wordlist::default();
+        let words = 7776f64.log2();
+        assert_eq!(
+            5.0 * words,
+            PassphraseOptions::default().entropy(&wordlist, 5)
+        );
+        let options = PassphraseOptions::default()
+            .capitalized(Capitalization::One)
+            .with_digits(1);
+        let expected = 4.0 * words + 2.0 + 10f64.log2();
+        assert!((expected - options.entropy(&wordlist, 4)).abs() < 1e-9);
+        let short = PassphraseOptions::default().with_max_len(23);
+        assert!(short.entropy(&wordlist, 4) < 4.0 * words);
+    }
+
+    #[test]
+    fn test_pin() {
+        for trivial in ["00", "0000", "1234", "987654", "1212", "123123", "4545"] {
+            assert!(is_trivial_pin(trivial), "{trivial}");
+        }
+        for pin in ["5", "10", "1235", "2580", "1221", "90123"] {
+            assert!(!is_trivial_pin(pin), "{pin}");
+        }
+        for len in 1..8 {
+            let pin = generate_pin(len);
+            assert_eq!(len as usize, pin.len());
+            assert!(pin.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn test_pronounceable() {
+        let password = generate_pronounceable(9);
+        assert_eq!(9, password.len());
+        for (i, c) in password.chars().enumerate() {
+            let chars = if i % 2 == 0 { CONSONANTS } else { VOWELS };
+            assert!(chars.contains(c), "{password}");
+        }
+        let expected = 5.0 * 18f64.log2() + 4.0 * 5f64.log2();
+        assert!((expected - pronounceable_entropy(9)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tokens() -> anyhow::Result<()> {
+        let hex = GeneratorPolicy::hex().generate(32)?;
         assert!(
-            len > 5000,
-            "wordlist should have > 5000 unique entries; has only {}",
-            len
+            hex.chars()
+                .all(|c| c.is_ascii_hexdigit() && !c.is_uppercase())
         );
+        assert_eq!(128.0, GeneratorPolicy::hex().entropy(32));
+        let base64 = GeneratorPolicy::base64();
+        assert_eq!(64, base64.alphabet().len());
+        assert_eq!(192.0, base64.entropy(32));
+        Ok(())
     }
 }
@@ -1,6 +1,8 @@
 use anyhow::anyhow;
+use std::collections::HashMap;
 use std::path::Path;
 use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub fn init(store: &Path) -> anyhow::Result<()> {
     run_command(
@@ -49,12 +51,23 @@ pub fn edit(store: &Path, key: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn r#move(store: &Path, from_key: &str, to_key: &str) -> anyhow::Result<bool> {
+pub fn rotate(store: &Path, key: &str) -> anyhow::Result<()> {
+    if has_repository(store) && is_file_tracked(store, key) {
+        commit(store, &[key.to_string()], &format!("rotate {key}"))?;
+    }
+    Ok(())
+}
+
+/// Moves a tracked entry with `git mv`, replacing an entry at the new key if `overwrite` is set
+pub fn r#move(store: &Path, from_key: &str, to_key: &str, overwrite: bool) -> anyhow::Result<bool> {
     if has_repository(store) && is_file_tracked(store, from_key) {
+        let mut mv = git();
+        mv.arg("mv");
+        if overwrite {
+            mv.arg("--force");
+        }
         run_command(
-            git()
-                .arg("mv")
-                .arg(store.join(from_key).as_os_str())
+            mv.arg(store.join(from_key).as_os_str())
                 .arg(store.join(to_key).as_os_str()),
             store,
             true,
@@ -74,10 +87,37 @@ pub fn r#move(store: &Path, from_key: &str, to_key: &str) -> anyhow::Result<bool
     }
 }
 
+/// Commits the moves of the tracked ones of the given entries, which must already be renamed,
+/// staging all of them at once
+pub fn move_all(store: &Path, moves: &[(String, String)]) -> anyhow::Result<()> {
+    if !has_repository(store) {
+        return Ok(());
+    }
+    let tracked: Vec<_> = moves
+        .iter()
+        .filter(|(from_key, _)| is_file_tracked(store, from_key))
+        .collect();
+    if tracked.is_empty() {
+        return Ok(());
+    }
+    let keys: Vec<_> = tracked
+        .iter()
+        .flat_map(|(from_key, to_key)| [from_key.clone(), to_key.clone()])
+        .collect();
+    commit(store, &keys, &format!("move {} entries", tracked.len()))
+}
+
+pub fn copy(store: &Path, from_key: &str, to_key: &str, keys: &[String]) -> anyhow::Result<()> {
+    if has_repository(store) {
+        commit(store, keys, &format!("copy {from_key} to {to_key}"))?;
+    }
+    Ok(())
+}
+
 pub fn remove(store: &Path, key: &str) -> anyhow::Result<()> {
     if has_repository(store) && is_file_tracked(store, key) {
         run_command(
-            git().arg("rm").arg(store.join(key).as_os_str()),
+            git().args(["rm", "-r"]).arg(store.join(key).as_os_str()),
             store,
             true,
         )?;
@@ -90,6 +130,114 @@ pub fn remove(store: &Path, key: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub fn restore(store: &Path, key: &str) -> anyhow::Result<()> {
+    if has_repository(store) {
+        commit(store, &[key.to_string()], &format!("restore {key}"))?;
+    }
+    Ok(())
+}
+
+pub fn import(store: &Path, keys: &[String]) -> anyhow::Result<()> {
+    if has_repository(store) {
+        commit(store, keys, &format!("import {} entries", keys.len()))?;
+    }
+    Ok(())
+}
+
+pub fn convert(store: &Path, keys: &[String], backend: &str) -> anyhow::Result<()> {
+    if has_repository(store) {
+        commit(
+            store,
+            keys,
+            &format!("convert {} entries to {backend}", keys.len()),
+        )?;
+    }
+    Ok(())
+}
+
+/// Commits a changed recipient file together with the entries re-encrypted for it
+pub fn update_recipients(store: &Path, files: &[String], message: &str) -> anyhow::Result<()> {
+    if has_repository(store) {
+        commit(store, files, message)?;
+    }
+    Ok(())
+}
+
+pub fn last_commit_times(store: &Path) -> anyhow::Result<HashMap<String, SystemTime>> {
+    let mut times = HashMap::new();
+    if !has_repository(store) {
+        return Ok(times);
+    }
+    let log = command_output(
+        git().args([
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--format=%x00%ct",
+            "--name-only",
+            "--no-renames",
+        ]),
+        store,
+    )?;
+    for commit in log.split('\0').filter(|c| !c.is_empty()) {
+        let mut lines = commit.lines();
+        let Some(Ok(seconds)) = lines.next().map(|l| l.trim().parse::<u64>()) else {
+            continue;
+        };
+        for file in lines.filter(|l| !l.is_empty()) {
+            times
+                .entry(file.to_string())
+                .or_insert(UNIX_EPOCH + Duration::from_secs(seconds));
+        }
+    }
+    Ok(times)
+}
+
+/// Files of the repository which differ from the last commit
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Status {
+    pub untracked: Vec<String>,
+    pub uncommitted: Vec<String>,
+}
+
+/// The status of the repository of the store, or None if the store has none
+pub fn status(store: &Path) -> anyhow::Result<Option<Status>> {
+    if !has_repository(store) {
+        return Ok(None);
+    }
+    let output = command_output(
+        git().args(["status", "--porcelain=v1", "-z", "--untracked-files=all"]),
+        store,
+    )?;
+    let mut status = Status::default();
+    let mut records = output.split('\0');
+    while let Some(record) = records.next() {
+        let Some((code, file)) = record.split_at_checked(3) else {
+            continue;
+        };
+        if code.starts_with("??") {
+            status.untracked.push(file.to_string());
+        } else {
+            status.uncommitted.push(file.to_string());
+        }
+        // renames and copies are followed by the original path
+        if code.starts_with(['R', 'C']) {
+            records.next();
+        }
+    }
+    Ok(Some(status))
+}
+
+fn commit(store: &Path, keys: &[String], message: &str) -> anyhow::Result<()> {
+    let mut add = git();
+    add.arg("add").arg("--");
+    for key in keys {
+        add.arg(store.join(key).as_os_str());
+    }
+    run_command(&mut add, store, true)?;
+    run_command(git().args(["commit", "--message", message]), store, true)
+}
+
 fn run_command(command: &mut Command, store: &Path, inherit_io: bool) -> anyhow::Result<()> {
     let stdio = || match inherit_io {
         true => Stdio::inherit(),
@@ -114,6 +262,26 @@ fn run_command(command: &mut Command, store: &Path, inherit_io: bool) -> anyhow:
     }
 }
 
+fn command_output(command: &mut Command, store: &Path) -> anyhow::Result<String> {
+    let output = command
+        .current_dir(store)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()?;
+    if output.status.success() {
+        Ok(String::from_utf8(output.stdout)?)
+    } else {
+        Err(anyhow!(
+            "git failed with status {}",
+            output
+                .status
+                .code()
+                .map(|c| c.to_string())
+                .unwrap_or("unknown".to_string())
+        ))
+    }
+}
+
 fn has_repository(store: &Path) -> bool {
     store.join(".git").is_dir()
 }
@@ -1,10 +1,25 @@
+pub mod archive;
 mod backend;
+pub mod breach;
+mod config;
+mod entry;
 mod generate;
 pub mod git;
+mod profile;
 mod resolve;
+mod strength;
+mod template;
+pub mod trash;
+mod wordlist;
 
 pub use backend::*;
+pub use config::*;
+pub use entry::*;
 pub use generate::*;
+pub use profile::*;
 pub use resolve::*;
+pub use strength::*;
+pub use template::*;
+pub use wordlist::*;
 
 pub const ENT_STORE_ENV_VAR: &str = "ENT_STORE";
@@ -0,0 +1,326 @@
+use crate::{Capitalization, Placement};
+use crate::{
+    CharClass, GeneratorPolicy, PassphraseOptions, Wordlist, generate_pin, generate_pronounceable,
+    pin_entropy, pronounceable_entropy,
+};
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GeneratorType {
+    #[default]
+    Phrase,
+    Word,
+    Pin,
+    Pronounceable,
+    Hex,
+    Base64,
+}
+
+impl GeneratorType {
+    pub fn default_length(&self) -> u8 {
+        match self {
+            GeneratorType::Phrase => 7,
+            GeneratorType::Word => 20,
+            GeneratorType::Pin => 6,
+            GeneratorType::Pronounceable => 16,
+            GeneratorType::Hex | GeneratorType::Base64 => 32,
+        }
+    }
+}
+
+/// Settings for generating passwords; unset options fall back to defaults.
+/// The options are named like the corresponding flags of `ent generate`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct GeneratorProfile {
+    pub r#type: Option<GeneratorType>,
+    pub length: Option<u8>,
+    /// The name of a builtin wordlist or the path to a wordlist file, relative to the store
+    pub wordlist: Option<String>,
+    pub sep: Option<String>,
+    pub capitalize: Option<Capitalization>,
+    pub digits: Option<u8>,
+    pub symbols: Option<u8>,
+    pub insert: Option<Placement>,
+    pub max_length: Option<usize>,
+    pub no_lower: Option<bool>,
+    pub no_upper: Option<bool>,
+    pub no_digits: Option<bool>,
+    pub no_symbols: Option<bool>,
+    pub custom: Option<String>,
+    pub exclude_ambiguous: Option<bool>,
+    pub exclude: Option<String>,
+    pub min_lower: Option<u8>,
+    pub min_upper: Option<u8>,
+    pub min_digits: Option<u8>,
+    pub min_symbols: Option<u8>,
+    pub min_custom: Option<u8>,
+}
+
+macro_rules! or_fields {
+    ($profile:ident, $fallback:ident, $($field:ident),*) => {
+        GeneratorProfile {
+            $($field: $profile.$field.or_else(|| $fallback.$field.clone()),)*
+        }
+    };
+}
+
+impl GeneratorProfile {
+    /// Fills the options which are not set in this profile with those of the fallback.
+    /// If this profile sets another type than the fallback, which is the default type if it sets
+    /// none, the length and the options specific to the type of the fallback are not used
+    pub fn or(self, fallback: &GeneratorProfile) -> GeneratorProfile {
+        let fallback_type = fallback.generator_type();
+        let fallback = match self.r#type {
+            Some(r#type) if r#type != fallback_type => {
+                fallback.clone().without_options_of(fallback_type)
+            }
+            _ => fallback.clone(),
+        };
+        or_fields!(
+            self,
+            fallback,
+            r#type,
+            length,
+            wordlist,
+            sep,
+            capitalize,
+            digits,
+            symbols,
+            insert,
+            max_length,
+            no_lower,
+            no_upper,
+            no_digits,
+            no_symbols,
+            custom,
+            exclude_ambiguous,
+            exclude,
+            min_lower,
+            min_upper,
+            min_digits,
+            min_symbols,
+            min_custom
+        )
+    }
+
+    fn without_options_of(self, r#type: GeneratorType) -> GeneratorProfile {
+        let profile = GeneratorProfile {
+            length: None,
+            ..self
+        };
+        match r#type {
+            GeneratorType::Phrase => GeneratorProfile {
+                wordlist: None,
+                sep: None,
+                capitalize: None,
+                digits: None,
+                symbols: None,
+                insert: None,
+                max_length: None,
+                ..profile
+            },
+            GeneratorType::Word => GeneratorProfile {
+                no_lower: None,
+                no_upper: None,
+                no_digits: None,
+                no_symbols: None,
+                custom: None,
+                exclude_ambiguous: None,
+                exclude: None,
+                min_lower: None,
+                min_upper: None,
+                min_digits: None,
+                min_symbols: None,
+                min_custom: None,
+                ..profile
+            },
+            _ => profile,
+        }
+    }
+
+    pub fn generator_type(&self) -> GeneratorType {
+        self.r#type.unwrap_or_default()
+    }
+
+    pub fn length(&self) -> u8 {
+        self.length
+            .unwrap_or_else(|| self.generator_type().default_length())
+    }
+
+    pub fn policy(&self) -> GeneratorPolicy {
+        let classes = [
+            (CharClass::Lower, self.no_lower, self.min_lower),
+            (CharClass::Upper, self.no_upper, self.min_upper),
+            (CharClass::Digits, self.no_digits, self.min_digits),
+            (CharClass::Symbols, self.no_symbols, self.min_symbols),
+        ];
+        let mut policy =
+            classes
+                .into_iter()
+                .fold(GeneratorPolicy::empty(), |policy, (class, exclude, min)| {
+                    if exclude.unwrap_or_default() {
+                        policy
+                    } else {
+                        policy.with_class(class, min.unwrap_or_default())
+                    }
+                });
+        if let Some(custom) = &self.custom {
+            policy = policy.with_custom(custom, self.min_custom.unwrap_or_default());
+        }
+        if self.exclude_ambiguous.unwrap_or_default() {
+            policy = policy.excluding_ambiguous();
+        }
+        if let Some(exclude) = &self.exclude {
+            policy = policy.excluding(exclude);
+        }
+        policy
+    }
+
+    pub fn passphrase_options(&self) -> PassphraseOptions {
+        let options = PassphraseOptions::new(self.sep.as_deref().unwrap_or(" "))
+            .capitalized(self.capitalize.unwrap_or_default())
+            .with_digits(self.digits.unwrap_or_default())
+            .with_symbols(self.symbols.unwrap_or_default())
+            .placed(self.insert.unwrap_or_default());
+        match self.max_length {
+            Some(max_length) => options.with_max_len(max_length),
+            None => options,
+        }
+    }
+
+    /// Resolves the profile into a generator; a wordlist file is resolved relative to `base`
+    pub fn generator(&self, base: &Path) -> anyhow::Result<Generator> {
+        let len = self.length();
+        let kind = match self.generator_type() {
+            GeneratorType::Phrase => {
+                let wordlist = match &self.wordlist {
+                    Some(wordlist) => Wordlist::from_name_or_path(wordlist, base)?,
+                    None => Wordlist::default(),
+                };
+                GeneratorKind::Phrase(wordlist, self.passphrase_options())
+            }
+            GeneratorType::Word => GeneratorKind::Policy(self.policy()),
+            GeneratorType::Pin => GeneratorKind::Pin,
+            GeneratorType::Pronounceable => GeneratorKind::Pronounceable,
+            GeneratorType::Hex => GeneratorKind::Policy(GeneratorPolicy::hex()),
+            GeneratorType::Base64 => GeneratorKind::Policy(GeneratorPolicy::base64()),
+        };
+        Ok(Generator { kind, len })
+    }
+}
+
+/// Generates passwords as described by a [GeneratorProfile]
+#[derive(Clone, Debug)]
+pub struct Generator {
+    kind: GeneratorKind,
+    len: u8,
+}
+
+#[derive(Clone, Debug)]
+enum GeneratorKind {
+    Phrase(Wordlist, PassphraseOptions),
+    Policy(GeneratorPolicy),
+    Pin,
+    Pronounceable,
+}
+
+impl Generator {
+    pub fn generate(&self) -> anyhow::Result<String> {
+        match &self.kind {
+            GeneratorKind::Phrase(wordlist, options) => options.generate(wordlist, self.len),
+            GeneratorKind::Policy(policy) => policy.generate(self.len),
+            GeneratorKind::Pin => Ok(generate_pin(self.len)),
+            GeneratorKind::Pronounceable => Ok(generate_pronounceable(self.len)),
+        }
+    }
+
+    /// The entropy of a generated password in bits
+    pub fn entropy(&self) -> f64 {
+        match &self.kind {
+            GeneratorKind::Phrase(wordlist, options) => options.entropy(wordlist, self.len),
+            GeneratorKind::Policy(policy) => policy.entropy(self.len),
+            GeneratorKind::Pin => pin_entropy(self.len),
+            GeneratorKind::Pronounceable => pronounceable_entropy(self.len),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_or() {
+        let cli = GeneratorProfile {
+            length: Some(12),
+            no_symbols: Some(true),
+            ..Default::default()
+        };
+        let profile = GeneratorProfile {
+            r#type: Some(GeneratorType::Word),
+            length: Some(16),
+            exclude: Some("x".to_string()),
+            ..Default::default()
+        };
+        let merged = cli.or(&profile);
+        assert_eq!(GeneratorType::Word, merged.generator_type());
+        assert_eq!(12, merged.length());
+        assert_eq!(Some(true), merged.no_symbols);
+        assert_eq!(Some("x"), merged.exclude.as_deref());
+        assert_eq!(7, GeneratorProfile::default().length());
+
+        let cli = GeneratorProfile {
+            r#type: Some(GeneratorType::Phrase),
+            ..Default::default()
+        };
+        let profile = GeneratorProfile {
+            wordlist: Some("eff-short".to_string()),
+            ..profile
+        };
+        let merged = cli.or(&profile);
+        assert_eq!(GeneratorType::Phrase, merged.generator_type());
+        assert_eq!(7, merged.length());
+        assert_eq!(None, merged.exclude);
+        assert_eq!(Some("eff-short"), merged.wordlist.as_deref());
+
+        let cli = GeneratorProfile {
+            r#type: Some(GeneratorType::Pin),
+            ..Default::default()
+        };
+        let untyped = GeneratorProfile {
+            length: Some(16),
+            sep: Some("-".to_string()),
+            no_symbols: Some(true),
+            ..Default::default()
+        };
+        let merged = cli.or(&untyped);
+        assert_eq!(GeneratorType::Pin, merged.generator_type());
+        assert_eq!(6, merged.length());
+        assert_eq!(None, merged.sep);
+        assert_eq!(Some(true), merged.no_symbols);
+        let merged = GeneratorProfile::default().or(&untyped);
+        assert_eq!(16, merged.length());
+    }
+
+    #[test]
+    fn test_generator() -> anyhow::Result<()> {
+        let profile = GeneratorProfile {
+            r#type: Some(GeneratorType::Word),
+            length: Some(16),
+            no_symbols: Some(true),
+            min_digits: Some(2),
+            ..Default::default()
+        };
+        let generator = profile.generator(Path::new(""))?;
+        let password = generator.generate()?;
+        assert_eq!(16, password.len());
+        assert!(password.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert!(password.chars().filter(char::is_ascii_digit).count() >= 2);
+        let words = GeneratorProfile::default().generator(Path::new(""))?;
+        assert_eq!(7.0 * 7776f64.log2(), words.entropy());
+        Ok(())
+    }
+}
@@ -1,6 +1,11 @@
+use crate::StoreConfig;
 use anyhow::anyhow;
+use itertools::Itertools;
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::path;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use walkdir::WalkDir;
 
 pub struct Locations {
@@ -21,7 +26,11 @@ pub fn get_existing_locations(base: &Path) -> anyhow::Result<Locations> {
             .into_os_string()
             .into_string()
             .map_err(|_| anyhow!("Encountered invalid UTF-8"))?;
-        if path.starts_with('.') {
+        // hidden files like nested recipient files are not entries
+        if path
+            .split(['/', '\\'])
+            .any(|segment| segment.starts_with('.'))
+        {
             continue;
         }
         if cfg!(windows) {
@@ -41,17 +50,48 @@ pub fn get_existing_locations(base: &Path) -> anyhow::Result<Locations> {
     Ok(locations)
 }
 
+pub fn get_existing_keys(base: &Path, dir: Option<&str>) -> anyhow::Result<Vec<String>> {
+    let mut files = get_existing_locations(base)?.files;
+    files.sort();
+    let dir = match dir.map(|d| d.trim_end_matches('/')) {
+        Some(dir) if !dir.is_empty() => dir,
+        _ => return Ok(files),
+    };
+    if resolve_existing_location(base, dir, true)?.is_file() {
+        return Ok(vec![dir.to_string()]);
+    }
+    let prefix = format!("{dir}/");
+    Ok(files
+        .into_iter()
+        .filter(|f| f.starts_with(&prefix))
+        .collect())
+}
+
+/// Resolves a key of an existing entry, trying the [ResolveStrategy]s configured in the store
+/// if the key does not exist exactly. Directories are only resolved exactly.
+///
+/// Errors are [KeyError]s if the key does not resolve to exactly one entry
 pub fn resolve_existing_location(
     base: &Path,
     key: &str,
     can_be_dir: bool,
+) -> anyhow::Result<PathBuf> {
+    let strategies = StoreConfig::load(base)?.resolve.strategies;
+    resolve_existing_location_with(base, key, can_be_dir, &strategies)
+}
+
+pub fn resolve_existing_location_with(
+    base: &Path,
+    key: &str,
+    can_be_dir: bool,
+    strategies: &[ResolveStrategy],
 ) -> anyhow::Result<PathBuf> {
     let concat = base.join(key);
     if can_be_dir {
         return if concat.exists() {
             Ok(concat)
         } else {
-            Err(anyhow!("Key {key} does not exist"))
+            Err(KeyError::NotFound(key.to_string()).into())
         };
     }
     if concat.is_file() {
@@ -62,19 +102,146 @@ pub fn resolve_existing_location(
         return if pass.is_file() {
             Ok(pass)
         } else {
-            Err(anyhow!("{key} is a directory"))
+            Err(KeyError::IsDirectory(key.to_string()).into())
         };
     }
     let existing = get_existing_locations(base)?;
-    let candidates: Vec<_> = existing
+    // a directory containing a pass file is an entry of its own
+    let entries: Vec<(&str, String)> = existing
         .files
         .iter()
-        .filter(|&s| s.starts_with(key))
+        .map(|file| (file.as_str(), file.clone()))
+        .chain(existing.dirs.iter().filter_map(|dir| {
+            let pass = format!("{dir}pass");
+            existing
+                .files
+                .contains(&pass)
+                .then_some((dir.trim_end_matches('/'), pass))
+        }))
         .collect();
-    if candidates.len() == 1 {
-        return Ok(base.join(candidates[0]));
+    for strategy in strategies {
+        let candidates: Vec<_> = strategy
+            .filter(key, &entries)
+            .into_iter()
+            .sorted()
+            .dedup()
+            .collect();
+        match candidates.len() {
+            0 => continue,
+            1 => return Ok(base.join(&candidates[0])),
+            _ => {
+                return Err(KeyError::Ambiguous {
+                    key: key.to_string(),
+                    candidates,
+                }
+                .into());
+            }
+        }
+    }
+    Err(KeyError::NotFound(key.to_string()).into())
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResolveStrategy {
+    /// Keys starting with the given key
+    Prefix,
+    /// Keys equal to or else starting with the given key, ignoring case
+    CaseInsensitive,
+    /// Keys whose path segments are abbreviated by the segments of the given key, like `w/gh` for `work/github`;
+    /// a segment abbreviates another one if both start with the same character and it contains the rest in order
+    SegmentPrefix,
+    /// Keys containing the given key
+    Substring,
+}
+
+impl ResolveStrategy {
+    pub const ALL: [ResolveStrategy; 4] = [
+        ResolveStrategy::Prefix,
+        ResolveStrategy::CaseInsensitive,
+        ResolveStrategy::SegmentPrefix,
+        ResolveStrategy::Substring,
+    ];
+
+    /// The resolved keys of the entries whose names match the given key
+    fn filter(&self, key: &str, entries: &[(&str, String)]) -> Vec<String> {
+        let matching = |matches: &dyn Fn(&str) -> bool| -> Vec<String> {
+            entries
+                .iter()
+                .filter(|(name, _)| matches(name))
+                .map(|(_, resolved)| resolved.clone())
+                .collect()
+        };
+        match self {
+            ResolveStrategy::Prefix => matching(&|name| name.starts_with(key)),
+            ResolveStrategy::CaseInsensitive => {
+                let key = key.to_lowercase();
+                let equal = matching(&|name| name.to_lowercase() == key);
+                if equal.is_empty() {
+                    matching(&|name| name.to_lowercase().starts_with(&key))
+                } else {
+                    equal
+                }
+            }
+            ResolveStrategy::SegmentPrefix => {
+                let segments: Vec<_> = key.split('/').collect();
+                matching(&|name| {
+                    let name_segments: Vec<_> = name.split('/').collect();
+                    name_segments.len() == segments.len()
+                        && name_segments
+                            .iter()
+                            .zip(&segments)
+                            .all(|(n, s)| abbreviates(s, n))
+                })
+            }
+            ResolveStrategy::Substring => matching(&|name| name.contains(key)),
+        }
+    }
+}
+
+fn abbreviates(abbreviation: &str, word: &str) -> bool {
+    let mut word_chars = word.chars();
+    let mut chars = abbreviation.chars();
+    match chars.next() {
+        Some(first) if word_chars.next() == Some(first) => {}
+        _ => return false,
     }
-    Err(anyhow!("Key {key} does not exist"))
+    chars.all(|c| word_chars.any(|w| w == c))
+}
+
+/// The reasons why a key does not resolve to an entry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyError {
+    NotFound(String),
+    IsDirectory(String),
+    Ambiguous {
+        key: String,
+        candidates: Vec<String>,
+    },
+}
+
+impl Display for KeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyError::NotFound(key) => write!(f, "Key {key} does not exist"),
+            KeyError::IsDirectory(key) => write!(f, "{key} is a directory"),
+            KeyError::Ambiguous { key, candidates } => write!(
+                f,
+                "Key {key} is ambiguous; it matches {}",
+                candidates.join(", ")
+            ),
+        }
+    }
+}
+
+impl Error for KeyError {}
+
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && Path::new(key).components().all(|c| match c {
+            Component::Normal(name) => !name.to_string_lossy().starts_with('.'),
+            _ => false,
+        })
 }
 
 pub fn resolve_new_location(base: &Path, key: &str) -> anyhow::Result<PathBuf> {
@@ -0,0 +1,179 @@
+use crate::wordlist::EFF_LARGE;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+const COMMON_PASSWORDS: &[&str] = &[
+    "password", "qwerty", "qwertz", "azerty", "abc123", "letmein", "monkey", "dragon", "iloveyou",
+    "admin", "welcome", "login", "master", "sunshine", "princess", "football", "baseball",
+    "shadow", "superman", "batman", "trustno1", "hello", "freedom", "whatever", "qazwsx", "asdf",
+    "asdfgh", "zxcvbn", "secret", "starwars", "michael", "charlie", "passwort", "hallo",
+];
+
+const MAX_WORD_LEN: usize = 12;
+
+static DICTIONARY: LazyLock<HashSet<&'static str>> =
+    LazyLock::new(|| EFF_LARGE.lines().filter(|w| w.len() >= 3).collect());
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Strength {
+    pub entropy: f64,
+    pub score: u8,
+}
+
+impl Strength {
+    pub const MAX_SCORE: u8 = 4;
+
+    pub fn from_entropy(entropy: f64) -> Strength {
+        Strength {
+            entropy,
+            score: score(entropy),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self.score {
+            0 => "very weak",
+            1 => "weak",
+            2 => "fair",
+            3 => "strong",
+            _ => "very strong",
+        }
+    }
+}
+
+/// Estimates the entropy of a password by finding the cheapest decomposition into
+/// random characters, repetitions or sequences, and dictionary words
+pub fn estimate_strength(password: &str) -> Strength {
+    let chars: Vec<char> = password.chars().collect();
+    let char_bits = (pool_size(&chars) as f64).log2();
+    let dictionary_bits = (DICTIONARY.len() as f64).log2();
+    let common_bits = (COMMON_PASSWORDS.len() as f64).log2();
+
+    let mut bits = vec![0f64; chars.len() + 1];
+    for i in 0..chars.len() {
+        let mut cheapest = bits[i] + char_bits;
+        if i > 0 && is_repeat_or_sequence(chars[i - 1], chars[i]) {
+            cheapest = cheapest.min(bits[i] + 1.0);
+        }
+        for j in i.saturating_sub(MAX_WORD_LEN - 1)..i.saturating_sub(1) {
+            if let Some((word, penalty)) = normalize(&chars[j..=i]) {
+                if COMMON_PASSWORDS.contains(&word.as_str()) {
+                    cheapest = cheapest.min(bits[j] + common_bits + penalty);
+                } else if DICTIONARY.contains(word.as_str()) {
+                    cheapest = cheapest.min(bits[j] + dictionary_bits + penalty);
+                }
+            }
+        }
+        bits[i + 1] = cheapest;
+    }
+    Strength::from_entropy(bits[chars.len()])
+}
+
+fn pool_size(chars: &[char]) -> u32 {
+    let has = |f: fn(&char) -> bool| chars.iter().any(f);
+    let mut size = 0;
+    if has(char::is_ascii_lowercase) {
+        size += 26;
+    }
+    if has(char::is_ascii_uppercase) {
+        size += 26;
+    }
+    if has(char::is_ascii_digit) {
+        size += 10;
+    }
+    if has(|c| c.is_ascii_punctuation() || *c == ' ') {
+        size += 33;
+    }
+    if has(|c| !c.is_ascii()) {
+        size += 100;
+    }
+    size.max(1)
+}
+
+fn is_repeat_or_sequence(previous: char, current: char) -> bool {
+    (previous as i64 - current as i64).abs() <= 1
+}
+
+/// Lowercases the given characters and undoes common substitutions;
+/// the returned penalty accounts for the possible variations
+fn normalize(chars: &[char]) -> Option<(String, f64)> {
+    let mut penalty = 0.0;
+    if chars.iter().any(|c| c.is_uppercase()) {
+        penalty += 1.0;
+    }
+    let mut substituted = false;
+    let word = chars
+        .iter()
+        .map(|c| {
+            let normalized = match c.to_ascii_lowercase() {
+                '@' | '4' => 'a',
+                '3' => 'e',
+                '1' | '!' => 'i',
+                '0' => 'o',
+                '$' | '5' => 's',
+                '7' => 't',
+                other => other,
+            };
+            substituted |= normalized != c.to_ascii_lowercase();
+            normalized
+        })
+        .collect::<String>();
+    if substituted {
+        penalty += 1.0;
+    }
+    word.chars()
+        .all(|c| c.is_ascii_lowercase())
+        .then_some((word, penalty))
+}
+
+fn score(entropy: f64) -> u8 {
+    match entropy {
+        e if e < 28.0 => 0,
+        e if e < 36.0 => 1,
+        e if e < 60.0 => 2,
+        e if e < 80.0 => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weak() {
+        for password in [
+            "password",
+            "P@ssw0rd",
+            "123456789",
+            "aaaaaaaaaaaa",
+            "Dragon1",
+        ] {
+            let strength = estimate_strength(password);
+            assert!(
+                strength.score <= 1,
+                "{password} should be weak but has score {} ({} bits)",
+                strength.score,
+                strength.entropy
+            );
+        }
+    }
+
+    #[test]
+    fn test_strong() {
+        for password in ["x7#Lq!9vR@2mZp$W", "correct horse battery staple"] {
+            let strength = estimate_strength(password);
+            assert!(
+                strength.score >= 3,
+                "{password} should be strong but has score {} ({} bits)",
+                strength.score,
+                strength.entropy
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(0, estimate_strength("").score);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::Entry;
+use serde::Deserialize;
+
+/// Describes the prompts used to assemble a new entry
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Template {
+    /// The fields prompted for in order, stored as `name: value` lines
+    pub fields: Vec<String>,
+    /// Whether to prompt for free-form notes
+    pub notes: bool,
+    pub password: PasswordSource,
+    /// The generator profile for generated passwords (default: the profile mapped to the key)
+    pub profile: Option<String>,
+}
+
+/// How the password of an entry created from a [Template] is obtained
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PasswordSource {
+    /// Ask whether to generate a password, and prompt for it otherwise
+    #[default]
+    Offer,
+    Prompt,
+    Generate,
+}
+
+impl Template {
+    pub const BUILTIN_NAMES: [&str; 3] = ["login", "server", "api-key"];
+
+    pub fn builtin(name: &str) -> Option<Template> {
+        let template = |fields: &[&str], password| Template {
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+            notes: true,
+            password,
+            profile: None,
+        };
+        match name {
+            "login" => Some(template(&["username", "url"], PasswordSource::Offer)),
+            "server" => Some(template(
+                &["host", "port", "username"],
+                PasswordSource::Offer,
+            )),
+            "api-key" => Some(template(&["url"], PasswordSource::Prompt)),
+            _ => None,
+        }
+    }
+
+    /// Assembles the content of an entry; fields without a value are left out
+    pub fn assemble(&self, password: &str, values: &[String], notes: &str) -> String {
+        let entry = Entry {
+            password: password.to_string(),
+            fields: self
+                .fields
+                .iter()
+                .zip(values)
+                .filter(|(_, value)| !value.trim().is_empty())
+                .map(|(name, value)| (name.clone(), value.trim().to_string()))
+                .collect(),
+            notes: notes
+                .lines()
+                .map(str::to_string)
+                .skip_while(|l| l.trim().is_empty())
+                .collect(),
+        };
+        let mut content = entry.to_string().trim_end().to_string();
+        content.push('\n');
+        content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin() {
+        for name in Template::BUILTIN_NAMES {
+            assert!(Template::builtin(name).is_some(), "{name}");
+        }
+        assert_eq!(None, Template::builtin("unknown"));
+    }
+
+    #[test]
+    fn test_assemble() {
+        let login = Template::builtin("login").unwrap();
+        let values = ["me".to_string(), " ".to_string()];
+        assert_eq!(
+            "secret\nusername: me\nfirst note\nsecond note\n",
+            login.assemble("secret", &values, "\nfirst note\nsecond note\n")
+        );
+        assert_eq!("secret\n", login.assemble("secret", &[], ""));
+    }
+}
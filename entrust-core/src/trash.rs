@@ -0,0 +1,229 @@
+use crate::is_valid_key;
+use anyhow::anyhow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
+
+/// The hidden directory of the store which removed entries are moved to.
+/// Every removal is kept in a subdirectory named after the seconds since the epoch at which it happened
+pub const TRASH_DIR: &str = ".trash";
+
+/// An entry in the trash
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrashedEntry {
+    pub key: String,
+    pub removed: SystemTime,
+}
+
+impl TrashedEntry {
+    /// Whether the entry was removed longer ago than the given duration; always true without one
+    pub fn is_expired(&self, older_than: Option<Duration>) -> bool {
+        is_expired(self.removed, older_than)
+    }
+}
+
+/// Moves the entry or directory of the given key into the trash, returning its new location
+pub fn trash(store: &Path, key: &str) -> anyhow::Result<PathBuf> {
+    let key = key.trim_end_matches('/');
+    let trash = store.join(TRASH_DIR);
+    if !trash.exists() {
+        fs::create_dir(&trash)?;
+        // the trash is never committed
+        fs::write(trash.join(".gitignore"), "*\n")?;
+    }
+    let mut seconds = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    while trash.join(seconds.to_string()).join(key).exists() {
+        seconds += 1;
+    }
+    let location = trash.join(seconds.to_string()).join(key);
+    if let Some(dir) = location.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::rename(store.join(key), &location)?;
+    Ok(location)
+}
+
+/// All entries in the trash, most recently removed first
+pub fn list(store: &Path) -> anyhow::Result<Vec<TrashedEntry>> {
+    let mut entries = Vec::new();
+    for (removed, dir) in removals(store)?.into_iter().rev() {
+        let mut keys = Vec::new();
+        for entry in WalkDir::new(&dir).min_depth(1) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let key = entry
+                    .path()
+                    .strip_prefix(&dir)?
+                    .to_string_lossy()
+                    .replace(std::path::MAIN_SEPARATOR, "/");
+                keys.push(key);
+            }
+        }
+        keys.sort();
+        entries.extend(keys.into_iter().map(|key| TrashedEntry { key, removed }));
+    }
+    Ok(entries)
+}
+
+/// Moves the most recently removed entry or directory of the given key back into the store
+pub fn restore(store: &Path, key: &str) -> anyhow::Result<PathBuf> {
+    let key = key.trim_end_matches('/');
+    if !is_valid_key(key) {
+        return Err(anyhow!("Invalid key: {key}"));
+    }
+    let (_, dir) = removals(store)?
+        .into_iter()
+        .rev()
+        .find(|(_, dir)| dir.join(key).exists())
+        .ok_or_else(|| anyhow!("Key {key} is not in the trash"))?;
+    let location = store.join(key);
+    if location.exists() {
+        return Err(anyhow!("Key {key} already exists"));
+    }
+    if let Some(parent) = location.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let trashed = dir.join(key);
+    fs::rename(&trashed, &location)?;
+    for parent in trashed.ancestors().skip(1) {
+        if !parent.starts_with(&dir) || parent.read_dir()?.next().is_some() {
+            break;
+        }
+        fs::remove_dir(parent)?;
+    }
+    Ok(location)
+}
+
+/// Permanently deletes the removals which happened longer ago than the given duration,
+/// or all of them if none is given; returns the number of deleted entries
+pub fn empty(store: &Path, older_than: Option<Duration>) -> anyhow::Result<usize> {
+    let mut deleted = 0;
+    for (removed, dir) in removals(store)? {
+        if is_expired(removed, older_than) {
+            deleted += WalkDir::new(&dir)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_file())
+                .count();
+            fs::remove_dir_all(dir)?;
+        }
+    }
+    Ok(deleted)
+}
+
+fn is_expired(removed: SystemTime, older_than: Option<Duration>) -> bool {
+    let age = SystemTime::now()
+        .duration_since(removed)
+        .unwrap_or_default();
+    older_than.is_none_or(|older_than| age > older_than)
+}
+
+/// The directories of all removals in the trash with their times, oldest first
+fn removals(store: &Path) -> anyhow::Result<Vec<(SystemTime, PathBuf)>> {
+    let trash = store.join(TRASH_DIR);
+    if !trash.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut removals = Vec::new();
+    for entry in fs::read_dir(trash)? {
+        let entry = entry?;
+        let Some(seconds) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        if entry.file_type()?.is_dir() {
+            removals.push((UNIX_EPOCH + Duration::from_secs(seconds), entry.path()));
+        }
+    }
+    removals.sort();
+    Ok(removals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_existing_keys;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    fn setup() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("work")).unwrap();
+        File::create(dir.path().join("work/github")).unwrap();
+        File::create(dir.path().join("work/jira")).unwrap();
+        File::create(dir.path().join("mail")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_trash_and_restore() {
+        let dir = setup();
+        let store = dir.path();
+        trash(store, "work").unwrap();
+        trash(store, "mail").unwrap();
+        assert!(get_existing_keys(store, None).unwrap().is_empty());
+        let keys: Vec<_> = list(store).unwrap().into_iter().map(|e| e.key).collect();
+        assert_eq!(vec!["mail", "work/github", "work/jira"], keys);
+
+        restore(store, "work/jira").unwrap();
+        restore(store, "mail").unwrap();
+        assert_eq!(
+            vec!["mail", "work/jira"],
+            get_existing_keys(store, None).unwrap()
+        );
+        assert!(restore(store, "mail").is_err());
+        let keys: Vec<_> = list(store).unwrap().into_iter().map(|e| e.key).collect();
+        assert_eq!(vec!["work/github"], keys);
+    }
+
+    #[test]
+    fn test_restore_existing() {
+        let dir = setup();
+        let store = dir.path();
+        trash(store, "mail").unwrap();
+        File::create(store.join("mail")).unwrap();
+        assert!(restore(store, "mail").is_err_and(|e| e.to_string().contains("already exists")));
+    }
+
+    #[test]
+    fn test_restore_invalid_key() {
+        let dir = setup();
+        let store = dir.path();
+        trash(store, "mail").unwrap();
+        let removal = fs::read_dir(store.join(TRASH_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .find(|name| name != ".gitignore")
+            .unwrap();
+        for key in [
+            format!("../{removal}"),
+            "../.gitignore".to_string(),
+            ".trash".to_string(),
+        ] {
+            assert!(restore(store, &key).is_err_and(|e| e.to_string().contains("Invalid key")));
+        }
+        assert!(store.join(TRASH_DIR).join(".gitignore").is_file());
+        assert!(store.join(TRASH_DIR).join(removal).join("mail").is_file());
+    }
+
+    #[test]
+    fn test_empty() {
+        let dir = setup();
+        let store = dir.path();
+        trash(store, "work").unwrap();
+        let entries = list(store).unwrap();
+        assert!(entries.iter().all(|e| e.is_expired(None)));
+        assert!(
+            !entries
+                .iter()
+                .any(|e| e.is_expired(Some(Duration::from_secs(60))))
+        );
+        assert_eq!(0, empty(store, Some(Duration::from_secs(60))).unwrap());
+        assert_eq!(2, empty(store, None).unwrap());
+        assert!(list(store).unwrap().is_empty());
+    }
+}
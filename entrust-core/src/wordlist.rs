@@ -0,0 +1,195 @@
+use anyhow::{Context, anyhow};
+use itertools::Itertools;
+use rand::prelude::IndexedRandom;
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+pub(crate) const EFF_LARGE: &str = include_str!("../eff_large.wordlist");
+const EFF_SHORT: &str = include_str!("../eff_short_1.wordlist");
+const EFF_SHORT_PREFIX: &str = include_str!("../eff_short_2.wordlist");
+
+/// The minimum number of unique words a wordlist must contain
+pub const MIN_WORDLIST_LEN: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuiltinWordlist {
+    /// The EFF large wordlist with 7776 words
+    EffLarge,
+    /// The first EFF short wordlist with 1296 short words
+    EffShort,
+    /// The second EFF short wordlist with 1296 words, each with a unique three-character prefix
+    EffShortPrefix,
+}
+
+impl BuiltinWordlist {
+    pub const ALL: [BuiltinWordlist; 3] = [
+        BuiltinWordlist::EffLarge,
+        BuiltinWordlist::EffShort,
+        BuiltinWordlist::EffShortPrefix,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinWordlist::EffLarge => "eff-large",
+            BuiltinWordlist::EffShort => "eff-short",
+            BuiltinWordlist::EffShortPrefix => "eff-short-prefix",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<BuiltinWordlist> {
+        Self::ALL.into_iter().find(|b| b.name() == name)
+    }
+
+    fn content(&self) -> &'static str {
+        match self {
+            BuiltinWordlist::EffLarge => EFF_LARGE,
+            BuiltinWordlist::EffShort => EFF_SHORT,
+            BuiltinWordlist::EffShortPrefix => EFF_SHORT_PREFIX,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wordlist {
+    words: Vec<Cow<'static, str>>,
+}
+
+impl Default for Wordlist {
+    fn default() -> Self {
+        Wordlist::builtin(BuiltinWordlist::EffLarge)
+    }
+}
+
+impl Wordlist {
+    pub fn builtin(builtin: BuiltinWordlist) -> Self {
+        Wordlist {
+            words: builtin.content().lines().map(Cow::Borrowed).collect(),
+        }
+    }
+
+    /// Parses a wordlist with one word per line.
+    /// Lines may be prefixed with diceware numbers, as in `11111 word`; empty lines are ignored.
+    /// Fails if a word is contained more than once or the list is shorter than [MIN_WORDLIST_LEN]
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        let mut words = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let word = match line.split_whitespace().collect_vec()[..] {
+                [] => continue,
+                [word] => word,
+                [number, word] if number.chars().all(|c| c.is_ascii_digit()) => word,
+                _ => return Err(anyhow!("Line {} contains more than one word", index + 1)),
+            };
+            words.push(Cow::Owned(word.to_string()));
+        }
+        if let Some(duplicate) = words.iter().duplicates().next() {
+            return Err(anyhow!("The word {duplicate} is contained more than once"));
+        }
+        if words.len() < MIN_WORDLIST_LEN {
+            return Err(anyhow!(
+                "The wordlist contains only {} words; at least {MIN_WORDLIST_LEN} are required",
+                words.len()
+            ));
+        }
+        Ok(Wordlist { words })
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Could not read wordlist {}", path.display()))?;
+        Wordlist::parse(&content).with_context(|| format!("Invalid wordlist {}", path.display()))
+    }
+
+    /// Loads the builtin wordlist with the given name
+    /// or else the file at the given path, relative to `base`
+    pub fn from_name_or_path(name_or_path: &str, base: &Path) -> anyhow::Result<Self> {
+        match BuiltinWordlist::from_name(name_or_path) {
+            Some(builtin) => Ok(Wordlist::builtin(builtin)),
+            None => Wordlist::from_file(&base.join(name_or_path)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The entropy of a passphrase with the given number of words in bits
+    pub fn entropy(&self, words: u8) -> f64 {
+        words as f64 * (self.len() as f64).log2()
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().map(Cow::as_ref)
+    }
+
+    pub fn random_word(&self) -> &str {
+        self.words.choose(&mut rand::rng()).unwrap()
+    }
+
+    pub fn generate_passphrase(&self, len: u8, separator: &str) -> String {
+        let phrase_iterable = (0..len).map(|_| self.random_word());
+        itertools::intersperse(phrase_iterable, separator).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_wordlists() {
+        for builtin in BuiltinWordlist::ALL {
+            let parsed = Wordlist::parse(builtin.content())
+                .unwrap_or_else(|e| panic!("{} is invalid: {e}", builtin.name()));
+            assert_eq!(Wordlist::builtin(builtin), parsed);
+            assert_eq!(Some(builtin), BuiltinWordlist::from_name(builtin.name()));
+        }
+        assert!(Wordlist::default().len() > 5000);
+        let prefixes = Wordlist::builtin(BuiltinWordlist::EffShortPrefix)
+            .words
+            .iter()
+            .map(|w| w.chars().take(3).collect::<String>())
+            .all_unique();
+        assert!(prefixes);
+    }
+
+    #[test]
+    fn test_parse() {
+        let words = (0..MIN_WORDLIST_LEN).map(|i| format!("w{i}")).collect_vec();
+        let diceware = words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| format!("{i}\t{w}"))
+            .join("\n");
+        let plain = words.join("\n\n");
+        assert_eq!(
+            Wordlist::parse(&diceware).unwrap(),
+            Wordlist::parse(&plain).unwrap()
+        );
+        assert_eq!(MIN_WORDLIST_LEN, Wordlist::parse(&plain).unwrap().len());
+
+        let too_short = words[1..].join("\n");
+        assert!(Wordlist::parse(&too_short).is_err());
+        let duplicate = format!("{plain}\nw0");
+        assert!(Wordlist::parse(&duplicate).is_err());
+        let multiple_words = format!("{plain}\ntwo words");
+        assert!(Wordlist::parse(&multiple_words).is_err());
+    }
+
+    #[test]
+    fn test_passphrase() {
+        let wordlist = Wordlist::builtin(BuiltinWordlist::EffShort);
+        let phrase = wordlist.generate_passphrase(5, "-");
+        assert_eq!(5, phrase.split('-').count());
+        assert!(
+            phrase
+                .split('-')
+                .all(|w| wordlist.words.iter().any(|x| x == w))
+        );
+        assert_eq!(5.0 * 1296f64.log2(), wordlist.entropy(5));
+    }
+}
@@ -1,15 +1,18 @@
+use entrust_core::{Backend, KeyError, ResolveStrategy};
 use std::fs::File;
 use std::{fs, io};
 use tempfile::{TempDir, tempdir};
 
 fn setup_test_store() -> io::Result<TempDir> {
     let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join(".gpg-id"), "root@example.com")?;
     File::create(temp_dir.path().join("file1"))?;
     File::create(temp_dir.path().join("file2"))?;
     let dir1 = temp_dir.path().join("dir1");
     fs::create_dir_all(&dir1)?;
     File::create(dir1.join("file1"))?;
     File::create(dir1.join("file2"))?;
+    fs::write(dir1.join(".gpg-id"), "dir1@example.com")?;
     let dir2 = temp_dir.path().join("dir2");
     fs::create_dir_all(&dir2)?;
     File::create(dir2.join("pass"))?;
@@ -31,6 +34,38 @@ fn test_get_all() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_recipient_file() -> anyhow::Result<()> {
+    let test_store = setup_test_store()?;
+    let store = test_store.path();
+    let recipients = |key: &str| Backend::Gpg.applicable_recipients(store, &store.join(key));
+    assert_eq!(vec!["dir1@example.com"], recipients("dir1/file1")?);
+    assert_eq!(vec!["root@example.com"], recipients("dir2/pass")?);
+    assert_eq!(vec!["root@example.com"], recipients("file1")?);
+    assert!(
+        Backend::Age
+            .recipient_file(store, &store.join("file1"))
+            .is_none()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_get_keys() -> anyhow::Result<()> {
+    let test_store = setup_test_store()?;
+    let all = entrust_core::get_existing_keys(test_store.path(), None)?;
+    assert_eq!(
+        vec!["dir1/file1", "dir1/file2", "dir2/pass", "file1", "file2"],
+        all
+    );
+    let dir1 = entrust_core::get_existing_keys(test_store.path(), Some("dir1/"))?;
+    assert_eq!(vec!["dir1/file1", "dir1/file2"], dir1);
+    let file1 = entrust_core::get_existing_keys(test_store.path(), Some("file1"))?;
+    assert_eq!(vec!["file1"], file1);
+    assert!(entrust_core::get_existing_keys(test_store.path(), Some("no such dir")).is_err());
+    Ok(())
+}
+
 #[test]
 fn test_resolve_existing() -> anyhow::Result<()> {
     let test_store = setup_test_store()?;
@@ -57,6 +92,64 @@ fn test_resolve_existing() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_resolve_strategies() -> anyhow::Result<()> {
+    let test_store = setup_test_store()?;
+    let base = test_store.path();
+    fs::create_dir_all(base.join("work/github"))?;
+    File::create(base.join("work/github/pass"))?;
+    File::create(base.join("work/github/user"))?;
+    File::create(base.join("work/gitlab"))?;
+    let resolve = |key: &str, strategies: &[ResolveStrategy]| {
+        entrust_core::resolve_existing_location_with(base, key, false, strategies)
+            .map(|l| l.strip_prefix(base).unwrap().to_string_lossy().into_owned())
+    };
+    let key_error = |result: anyhow::Result<String>| result.unwrap_err().downcast::<KeyError>();
+
+    assert_eq!("dir2/pass", resolve("dir2", &[])?);
+    assert_eq!(
+        "work/gitlab",
+        resolve("work/gitl", &[ResolveStrategy::Prefix])?
+    );
+    assert_eq!(
+        KeyError::Ambiguous {
+            key: "work/git".to_string(),
+            candidates: vec![
+                "work/github/pass".to_string(),
+                "work/github/user".to_string(),
+                "work/gitlab".to_string()
+            ]
+        },
+        key_error(resolve("work/git", &ResolveStrategy::ALL))?
+    );
+    assert_eq!(
+        KeyError::NotFound("WORK/GITL".to_string()),
+        key_error(resolve("WORK/GITL", &[ResolveStrategy::Prefix]))?
+    );
+    assert_eq!(
+        "work/gitlab",
+        resolve("WORK/GITL", &[ResolveStrategy::CaseInsensitive])?
+    );
+    assert_eq!(
+        "work/github/pass",
+        resolve("w/gh", &[ResolveStrategy::SegmentPrefix])?
+    );
+    assert_eq!(
+        "work/github/user",
+        resolve("w/gi/u", &[ResolveStrategy::SegmentPrefix])?
+    );
+    assert_eq!(
+        "work/gitlab",
+        resolve("lab", &[ResolveStrategy::Substring])?
+    );
+    assert_eq!("work/gitlab", resolve("lab", &ResolveStrategy::ALL)?);
+    assert_eq!(
+        KeyError::IsDirectory("work".to_string()),
+        key_error(resolve("work", &ResolveStrategy::ALL))?
+    );
+    Ok(())
+}
+
 #[test]
 fn test_resolve_new() -> anyhow::Result<()> {
     let test_store = setup_test_store()?;
@@ -71,3 +164,12 @@ fn test_resolve_new() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_is_valid_key() {
+    assert!(entrust_core::is_valid_key("dir/key"));
+    assert!(!entrust_core::is_valid_key(""));
+    assert!(!entrust_core::is_valid_key("/etc/passwd"));
+    assert!(!entrust_core::is_valid_key("../outside"));
+    assert!(!entrust_core::is_valid_key("dir/.age-id"));
+}
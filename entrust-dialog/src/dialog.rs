@@ -2,6 +2,8 @@ use ratatui::backend::CrosstermBackend;
 use ratatui::crossterm::event;
 use ratatui::crossterm::event::Event;
 use ratatui::{Frame, Terminal, TerminalOptions, Viewport, crossterm};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::io;
 use std::time::{Duration, Instant};
 
@@ -56,7 +58,7 @@ pub trait Dialog: Sized {
         let result = if timed_out {
             Err(io::Error::other("timed out"))
         } else if self.state() == DialogState::Cancelled {
-            Err(io::Error::other("cancelled"))
+            Err(io::Error::other(Cancelled))
         } else {
             Ok(self.output())
         };
@@ -74,6 +76,18 @@ pub trait Dialog: Sized {
     }
 }
 
+/// The error wrapped in the [io::Error] returned by [Dialog::run] if the user cancelled the dialog
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum DialogState {
     #[default]
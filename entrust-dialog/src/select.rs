@@ -12,7 +12,7 @@ use std::borrow::Cow;
 use std::env;
 use tracing::debug;
 
-mod filter;
+pub mod filter;
 mod widget;
 
 #[derive(Debug)]
@@ -11,6 +11,39 @@ pub(super) struct FilteredItem<'a> {
     pub(super) matching_chars: Vec<usize>,
 }
 
+/// An item matching a fuzzy query
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch<'a> {
+    pub content: &'a str,
+    /// The index of the item in the given items
+    pub index: usize,
+    pub score: i64,
+    /// The indices of the characters matching the query
+    pub matching_chars: Vec<usize>,
+}
+
+/// Matches the items against the query with the fuzzy matcher used by [SelectDialog](super::SelectDialog)
+/// and returns the matching ones, best match first; ties keep the order of the items
+pub fn rank<'a, S: AsRef<str>>(items: &'a [S], query: &str) -> Vec<FuzzyMatch<'a>> {
+    let mut matches: Vec<_> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let content = item.as_ref();
+            MATCHER
+                .fuzzy_indices(content, query)
+                .map(|(score, matching_chars)| FuzzyMatch {
+                    content,
+                    index,
+                    score,
+                    matching_chars,
+                })
+        })
+        .collect();
+    matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+    matches
+}
+
 pub(super) fn apply_filter<'a>(
     list: &'a [Item<'a>],
     list_state: &mut ListState,
@@ -40,3 +73,25 @@ pub(super) fn get_filtered<'a>(list: &'a [Item<'a>], filter: &str) -> Vec<Filter
         .filter(|i| filter.is_empty() || !i.matching_chars.is_empty())
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank() {
+        let keys = [
+            "personal/gitlab",
+            "work/github/pass",
+            "work/gh/token",
+            "bank/pin",
+        ];
+        let ranked = rank(&keys, "gith");
+        assert_eq!("work/github/pass", ranked[0].content);
+        assert_eq!(1, ranked[0].index);
+        assert!(ranked.iter().all(|m| m.content != "bank/pin"));
+        assert!(ranked.windows(2).all(|w| w[0].score >= w[1].score));
+        assert_eq!(4, rank(&keys, "").len());
+        assert!(rank(&keys, "xyz").is_empty());
+    }
+}
@@ -6,12 +6,21 @@ use clap::Parser;
 use entrust::alias::apply_aliases;
 use entrust::command;
 use entrust::command::EntArgs;
+use entrust::error::report;
 use std::env;
+use std::process::ExitCode;
 
-fn main() -> anyhow::Result<()> {
-    #[cfg(feature = "tracing")]
-    tracing::init_tracing()?;
+fn main() -> ExitCode {
     let mut args: Vec<_> = env::args().collect();
     apply_aliases(&mut args);
-    command::run(EntArgs::parse_from(args))
+    let args = EntArgs::parse_from(args);
+    let json = args.json;
+    #[cfg(feature = "tracing")]
+    if let Err(error) = tracing::init_tracing() {
+        return report(&error, json);
+    }
+    match command::run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => report(&error, json),
+    }
 }
@@ -0,0 +1,115 @@
+use entrust_core::Backend;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::num::NonZero;
+use std::path::Path;
+use std::thread;
+
+/// Decrypts the entries in parallel, returning the results in the order of the keys
+pub(crate) fn decrypt_all(store: &Path, keys: &[String]) -> Vec<anyhow::Result<String>> {
+    let decrypt = |key: &String| Backend::decrypt(&store.join(key));
+    // the first decryption with each backend may prompt for a passphrase, read the age identity
+    // or start the agent, all of which are cached for the following ones
+    let mut backends = Vec::new();
+    let (first, rest): (Vec<_>, Vec<_>) = keys.iter().enumerate().partition(|(_, key)| {
+        let backend = Backend::detect(&store.join(key)).ok();
+        let first = !backends.contains(&backend);
+        if first {
+            backends.push(backend);
+        }
+        first
+    });
+    let mut results: Vec<_> = first
+        .into_iter()
+        .map(|(i, key)| (i, decrypt(key)))
+        .collect();
+    let threads = thread::available_parallelism().map_or(1, NonZero::get);
+    let chunk_size = rest.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = rest
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(i, key)| (*i, decrypt(key)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        for handle in handles {
+            results.extend(handle.join().expect("decryption thread panicked"));
+        }
+    });
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Decrypts all entries before anything is changed, failing if any of them cannot be decrypted
+pub(crate) fn decrypt_all_or_abort(store: &Path, keys: &[String]) -> anyhow::Result<Vec<String>> {
+    decrypt_all(store, keys)
+        .into_iter()
+        .zip(keys)
+        .map(|(decrypted, key)| {
+            decrypted
+                .map_err(|e| e.context(format!("Could not decrypt {key}; nothing was changed")))
+        })
+        .collect()
+}
+
+/// Encrypts the decrypted contents over the entries with the given backend and prints the progress;
+/// stops at the first entry which cannot be encrypted
+pub(crate) fn reencrypt_all(
+    store: &Path,
+    backend: Backend,
+    keys: &[String],
+    contents: Vec<String>,
+) -> Result<(), ReencryptError> {
+    let total = keys.len();
+    let width = total.to_string().len();
+    for (i, (key, content)) in keys.iter().zip(contents).enumerate() {
+        if let Err(error) = backend.encrypt_replacing(content.as_bytes(), store, &store.join(key)) {
+            return Err(ReencryptError {
+                reencrypted: i,
+                left: keys[i..].to_vec(),
+                error,
+            });
+        }
+        eprintln!("[{:>width$}/{total}] {key}", i + 1);
+    }
+    Ok(())
+}
+
+/// The failure of [reencrypt_all] after the given number of entries were re-encrypted
+#[derive(Debug)]
+pub(crate) struct ReencryptError {
+    reencrypted: usize,
+    left: Vec<String>,
+    error: anyhow::Error,
+}
+
+impl Display for ReencryptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Could not re-encrypt {}; {} of {} entries were re-encrypted, these were not: {}",
+            self.left[0],
+            self.reencrypted,
+            self.reencrypted + self.left.len(),
+            self.left.join(", ")
+        )
+    }
+}
+
+impl Error for ReencryptError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
+/// Whether the error occurred after some entries were re-encrypted, which then
+/// require the changed recipient files
+pub(crate) fn reencrypted_any(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ReencryptError>()
+        .is_some_and(|e| e.reencrypted > 0)
+}
@@ -1,29 +1,57 @@
 pub mod add;
+pub mod audit;
 #[cfg(feature = "autotype")]
 mod autotype;
+pub mod breach_check;
 mod clip;
 pub mod completions;
+pub mod convert;
+pub mod copy;
+pub mod doctor;
 pub mod edit;
+pub mod export;
+pub mod find;
 pub mod generate;
 pub mod get;
 mod git;
-mod identity;
+pub mod grep;
+pub mod identity;
+pub mod import;
 pub mod r#move;
+pub mod recipients;
 pub mod remove;
+pub mod restore;
+pub mod rotate;
 mod shell;
+pub mod trash;
+pub mod verify;
 
 use crate::command::add::AddArgs;
+use crate::command::audit::AuditArgs;
 #[cfg(feature = "autotype")]
 use crate::command::autotype::AutotypeArgs;
+use crate::command::breach_check::BreachCheckArgs;
 use crate::command::clip::ClipArgs;
 use crate::command::completions::CompletionsArgs;
+use crate::command::convert::ConvertArgs;
+use crate::command::copy::CopyArgs;
 use crate::command::edit::EditArgs;
+use crate::command::export::ExportArgs;
+use crate::command::find::FindArgs;
 use crate::command::generate::GenerateArgs;
 use crate::command::get::GetArgs;
 use crate::command::git::GitArgs;
+use crate::command::grep::GrepArgs;
+use crate::command::identity::IdentityArgs;
+use crate::command::import::ImportArgs;
 use crate::command::r#move::MoveArgs;
+use crate::command::recipients::RecipientsArgs;
 use crate::command::remove::RemoveArgs;
-use crate::tree::print_tree;
+use crate::command::restore::RestoreArgs;
+use crate::command::rotate::RotateArgs;
+use crate::command::trash::TrashArgs;
+use crate::command::verify::VerifyArgs;
+use crate::tree::{print_json_tree, print_tree};
 use crate::{init, theme};
 use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use color_print::cstr;
@@ -55,6 +83,9 @@ pub struct EntArgs {
     /// The directory in which the encrypted passwords are stored
     #[arg(short, long, env = entrust_core::ENT_STORE_ENV_VAR, value_name = "DIR", value_parser = parse_store)]
     pub store: PathBuf,
+    /// Print the output of commands like get, tree and find and any error as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
 }
 
 fn parse_store(string: &str) -> Result<PathBuf, String> {
@@ -87,15 +118,45 @@ pub enum EntSubcommand {
     Get(GetArgs),
     #[command(about = edit::ABOUT, long_about = edit::LONG_ABOUT)]
     Edit(EditArgs),
-    #[command(about = r#move::ABOUT, alias = "mv")]
+    #[command(about = r#move::ABOUT, long_about = r#move::LONG_ABOUT, alias = "mv")]
     Move(MoveArgs),
-    #[command(about = remove::ABOUT, alias = "rm")]
+    #[command(about = copy::ABOUT, long_about = copy::LONG_ABOUT, alias = "cp")]
+    Copy(CopyArgs),
+    #[command(about = remove::ABOUT, long_about = remove::LONG_ABOUT, alias = "rm")]
     Remove(RemoveArgs),
+    #[command(about = restore::ABOUT)]
+    Restore(RestoreArgs),
+    #[command(about = trash::ABOUT, long_about = trash::LONG_ABOUT)]
+    Trash(TrashArgs),
     #[command(about = generate::ABOUT, alias = "gen")]
     Generate(GenerateArgs),
+    #[command(about = rotate::ABOUT, long_about = rotate::LONG_ABOUT)]
+    Rotate(RotateArgs),
+    #[command(about = find::ABOUT, long_about = find::LONG_ABOUT)]
+    Find(FindArgs),
+    #[command(about = grep::ABOUT, long_about = grep::LONG_ABOUT)]
+    Grep(GrepArgs),
+    #[command(about = audit::ABOUT, long_about = audit::LONG_ABOUT)]
+    Audit(AuditArgs),
+    #[command(about = verify::ABOUT, long_about = verify::LONG_ABOUT)]
+    Verify(VerifyArgs),
+    #[command(about = recipients::ABOUT, long_about = recipients::LONG_ABOUT)]
+    Recipients(RecipientsArgs),
+    #[command(about = identity::ABOUT, long_about = identity::LONG_ABOUT)]
+    Identity(IdentityArgs),
+    #[command(about = convert::ABOUT, long_about = convert::LONG_ABOUT)]
+    Convert(ConvertArgs),
+    #[command(about = breach_check::ABOUT, long_about = breach_check::LONG_ABOUT)]
+    BreachCheck(BreachCheckArgs),
+    #[command(about = export::ABOUT, long_about = export::LONG_ABOUT)]
+    Export(ExportArgs),
+    #[command(about = import::ABOUT, long_about = import::LONG_ABOUT)]
+    Import(ImportArgs),
     #[cfg(feature = "autotype")]
     #[command(about = autotype::ABOUT, alias = "type")]
     Autotype(AutotypeArgs),
+    #[command(about = doctor::ABOUT, long_about = doctor::LONG_ABOUT)]
+    Doctor,
     #[command(about = completions::ABOUT)]
     Completions(CompletionsArgs),
     #[command(about = "Print a tree of the password store")]
@@ -106,8 +167,6 @@ pub enum EntSubcommand {
     Clip(ClipArgs),
     #[command(hide = true)]
     Shell,
-    #[command(hide = true)]
-    Identity,
 }
 
 pub fn run(ent: EntArgs) -> anyhow::Result<()> {
@@ -117,14 +176,28 @@ pub fn run(ent: EntArgs) -> anyhow::Result<()> {
         Some(EntSubcommand::Add(args)) => add::run(ent.store, args),
         Some(EntSubcommand::Clip(args)) => clip::run(args),
         Some(EntSubcommand::Edit(args)) => edit::run(ent.store, args),
-        Some(EntSubcommand::Generate(args)) => generate::run(ent.store, args),
-        Some(EntSubcommand::Get(args)) => get::run(ent.store, args),
+        Some(EntSubcommand::Generate(args)) => generate::run(ent.store, args, ent.json),
+        Some(EntSubcommand::Get(args)) => get::run(ent.store, args, ent.json),
+        Some(EntSubcommand::Find(args)) => find::run(ent.store, args, ent.json),
+        Some(EntSubcommand::Grep(args)) => grep::run(ent.store, args),
+        Some(EntSubcommand::Audit(args)) => audit::run(ent.store, args, ent.json),
+        Some(EntSubcommand::Verify(args)) => verify::run(ent.store, args, ent.json),
+        Some(EntSubcommand::Recipients(args)) => recipients::run(ent.store, args, ent.json),
+        Some(EntSubcommand::BreachCheck(args)) => breach_check::run(ent.store, args),
+        Some(EntSubcommand::Export(args)) => export::run(ent.store, args),
+        Some(EntSubcommand::Import(args)) => import::run(ent.store, args),
         Some(EntSubcommand::Move(args)) => r#move::run(ent.store, args),
+        Some(EntSubcommand::Copy(args)) => copy::run(ent.store, args),
         Some(EntSubcommand::Remove(args)) => remove::run(ent.store, args),
+        Some(EntSubcommand::Restore(args)) => restore::run(ent.store, args),
+        Some(EntSubcommand::Trash(args)) => trash::run(ent.store, args, ent.json),
+        Some(EntSubcommand::Rotate(args)) => rotate::run(ent.store, args),
         #[cfg(feature = "autotype")]
         Some(EntSubcommand::Autotype(args)) => autotype::run(ent.store, args),
+        Some(EntSubcommand::Tree) if ent.json => print_json_tree(&ent.store),
         Some(EntSubcommand::Tree) => print_tree(&ent.store),
         Some(EntSubcommand::Git(args)) => git::run(ent.store, args),
+        Some(EntSubcommand::Doctor) => doctor::run(ent.store),
         None => {
             EntArgs::command().print_help()?;
             print_tree(&ent.store)?;
@@ -135,7 +208,8 @@ pub fn run(ent: EntArgs) -> anyhow::Result<()> {
             Ok(())
         }
         Some(EntSubcommand::Shell) => shell::run(),
-        Some(EntSubcommand::Identity) => identity::run(),
+        Some(EntSubcommand::Identity(args)) => identity::run(ent.store, args),
+        Some(EntSubcommand::Convert(args)) => convert::run(ent.store, args),
     }
 }
 
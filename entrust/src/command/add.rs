@@ -1,3 +1,4 @@
+use anyhow::anyhow;
 use clap::Args;
 use color_print::cstr;
 use std::io::{IsTerminal, Read};
@@ -7,9 +8,10 @@ use std::{fs, io};
 use crate::command::BackendValueEnum;
 use crate::dialog;
 use crate::key::Key;
+use crate::strength::{MIN_STRENGTH_ENV_VAR, check_strength};
 use crate::theme::chevron_prompt;
 use entrust_core;
-use entrust_core::{Backend, git};
+use entrust_core::{Backend, PasswordSource, StoreConfig, Template, git, trash};
 
 pub(super) const ABOUT: &str = "Add a new password";
 
@@ -18,7 +20,11 @@ pub(super) const LONG_ABOUT: &str = cstr!(
 
   Add a new password
 
-  Reads from <bold,#ffb86c>stdin</> or offers an interactive prompt if stdin is empty"
+  Reads from <bold,#ffb86c>stdin</> or offers an interactive prompt if stdin is empty
+
+  With <bold,#ffb86c>--template</>, prompts for the fields of the template and offers to generate \
+  the password. Builtin templates are <bold,#ffb86c>login</>, <bold,#ffb86c>server</> and \
+  <bold,#ffb86c>api-key</>; more can be defined in <bold,#ffb86c>.ent.toml</>"
 );
 
 #[derive(Args, Debug)]
@@ -31,31 +37,129 @@ pub struct AddArgs {
     /// Do not add the new file to git
     #[arg(long = "no-git")]
     no_git: bool,
+    /// Reject passwords with an estimated strength score (0-4) below the given value
+    #[arg(long, env = MIN_STRENGTH_ENV_VAR, value_name = "SCORE",
+    value_parser = clap::value_parser!(u8).range(0..=4))]
+    min_strength: Option<u8>,
+    /// Assemble the entry by interactively prompting for the fields of the given template
+    #[arg(short, long, value_name = "NAME")]
+    template: Option<String>,
+    /// Overwrite an existing entry without asking for confirmation
+    #[arg(short, long)]
+    force: bool,
 }
 
 pub fn run(store: PathBuf, args: AddArgs) -> anyhow::Result<()> {
     let key = &args
         .key
         .unwrap_or_read_new(chevron_prompt!("Key"), &store)?;
-    encrypt(&store, key, args.backend.into())?;
+    let template = args
+        .template
+        .as_deref()
+        .map(|name| StoreConfig::load(&store)?.template(name))
+        .transpose()?;
+    let overwritten = encrypt(
+        &store,
+        key,
+        args.backend.into(),
+        args.min_strength,
+        template.as_ref(),
+        args.force,
+    )?;
     if !args.no_git {
-        git::add(&store, key)?
+        if overwritten {
+            git::edit(&store, key)?
+        } else {
+            git::add(&store, key)?
+        }
     }
     Ok(())
 }
 
-fn encrypt(store: &Path, key: &str, backend: Backend) -> anyhow::Result<()> {
-    let location = entrust_core::resolve_new_location(store, key)?;
-    if let Some(parent) = location.parent() {
+/// Encrypts the password read from stdin or a prompt under the key,
+/// returning whether an existing entry was overwritten
+fn encrypt(
+    store: &Path,
+    key: &str,
+    backend: Backend,
+    min_strength: Option<u8>,
+    template: Option<&Template>,
+    force: bool,
+) -> anyhow::Result<bool> {
+    let location = store.join(key);
+    let overwrite = dialog::trash_for_overwrite(store, key, force)?;
+    if !overwrite {
+        entrust_core::resolve_new_location(store, key)?;
+    }
+    let encrypted = read_input(store, key, min_strength, template)
+        .and_then(|input| backend.encrypt(input.as_bytes(), store, &location));
+    if let Err(err) = encrypted {
+        if overwrite {
+            if location.exists() {
+                fs::remove_file(&location)?;
+            }
+            trash::restore(store, key)?;
+        }
+        return Err(err);
+    }
+    Ok(overwrite)
+}
+
+fn read_input(
+    store: &Path,
+    key: &str,
+    min_strength: Option<u8>,
+    template: Option<&Template>,
+) -> anyhow::Result<String> {
+    if let Some(parent) = store.join(key).parent() {
         fs::create_dir_all(parent)?;
     }
-    let input = if io::stdin().is_terminal() {
-        dialog::read_password_interactive("")?
+    let input = if let Some(template) = template {
+        if !io::stdin().is_terminal() {
+            return Err(anyhow!("Templates require an interactive terminal"));
+        }
+        read_from_template(store, key, template, min_strength)?
+    } else if io::stdin().is_terminal() {
+        dialog::read_password_interactive("", min_strength)?
     } else {
         let mut input = String::new();
         io::stdin().read_to_string(&mut input)?;
         input
     };
-    backend.encrypt(input.as_bytes(), store, &location)?;
-    Ok(())
+    check_strength(&input, min_strength)?;
+    Ok(input)
+}
+
+fn read_from_template(
+    store: &Path,
+    key: &str,
+    template: &Template,
+    min_strength: Option<u8>,
+) -> anyhow::Result<String> {
+    let values = template
+        .fields
+        .iter()
+        .map(|field| dialog::read_field_interactive(field))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let generate = match template.password {
+        PasswordSource::Generate => true,
+        PasswordSource::Prompt => false,
+        PasswordSource::Offer => dialog::confirm("Generate a password?".to_string())?,
+    };
+    let password = if generate {
+        let config = StoreConfig::load(store)?;
+        let profile = match &template.profile {
+            Some(profile) => config.profile(profile)?,
+            None => config.profile_for_key(key)?,
+        };
+        profile.generator(store)?.generate()?
+    } else {
+        dialog::read_password_interactive("", min_strength)?
+    };
+    let notes = if template.notes {
+        dialog::read_notes_interactive()?
+    } else {
+        String::new()
+    };
+    Ok(template.assemble(&password, &values, &notes))
 }
@@ -0,0 +1,285 @@
+use crate::bulk::decrypt_all;
+use crate::theme::print_heading;
+use clap::Args;
+use color_print::cstr;
+use entrust_core::{Entry, Strength, estimate_strength, get_existing_keys, git};
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub(super) const ABOUT: &str = "Report weak, reused and outdated passwords";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Report weak, reused and outdated passwords
+
+  Decrypts all entries below <bold,#ffb86c>DIR</> and reports weak passwords, passwords \
+  used for multiple keys or very similar to each other, entries which have not been changed \
+  for a long time and entries which lack required fields"
+);
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Args, Debug)]
+pub struct AuditArgs {
+    /// The directory to audit (default: the whole store)
+    dir: Option<String>,
+    /// Report passwords with a strength score (0-4) below this value
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(0..=4))]
+    min_score: u8,
+    /// Report entries which have not been changed for more than the given number of days
+    #[arg(long, default_value_t = 365, value_name = "DAYS")]
+    max_age: u64,
+    /// Report entries which lack the given field; can be given multiple times
+    #[arg(short = 'f', long = "require-field", value_name = "FIELD")]
+    required_fields: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Default)]
+struct Report {
+    weak: Vec<Weak>,
+    reused: Vec<Vec<String>>,
+    similar: Vec<Vec<String>>,
+    stale: Vec<Stale>,
+    missing_fields: Vec<MissingFields>,
+    unreadable: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct Weak {
+    key: String,
+    score: u8,
+    entropy: f64,
+}
+
+#[derive(Serialize, Debug)]
+struct Stale {
+    key: String,
+    days: u64,
+}
+
+#[derive(Serialize, Debug)]
+struct MissingFields {
+    key: String,
+    fields: Vec<String>,
+}
+
+pub fn run(store: PathBuf, args: AuditArgs, json: bool) -> anyhow::Result<()> {
+    let keys = get_existing_keys(&store, args.dir.as_deref())?;
+    let mut report = Report::default();
+    let mut by_password: HashMap<String, Vec<String>> = HashMap::new();
+    let last_commits = git::last_commit_times(&store)?;
+    let now = SystemTime::now();
+
+    for (key, decrypted) in keys.iter().zip(decrypt_all(&store, &keys)) {
+        let Ok(content) = decrypted else {
+            report.unreadable.push(key.clone());
+            continue;
+        };
+        let entry = Entry::parse(&content);
+
+        let strength = estimate_strength(&entry.password);
+        if strength.score < args.min_score {
+            report.weak.push(weak(key, strength));
+        }
+
+        let missing: Vec<_> = args
+            .required_fields
+            .iter()
+            .filter(|&f| entry.field(f).is_none())
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            report.missing_fields.push(MissingFields {
+                key: key.clone(),
+                fields: missing,
+            });
+        }
+
+        let days = last_modified(&store, key, &last_commits)
+            .and_then(|m| now.duration_since(m).ok())
+            .map(|d| d.as_secs() / SECONDS_PER_DAY);
+        if let Some(days) = days.filter(|&d| d > args.max_age) {
+            report.stale.push(Stale {
+                key: key.clone(),
+                days,
+            });
+        }
+
+        if !entry.password.is_empty() {
+            by_password
+                .entry(entry.password)
+                .or_default()
+                .push(key.clone());
+        }
+    }
+
+    report.reused = by_password
+        .values()
+        .filter(|keys| keys.len() > 1)
+        .cloned()
+        .sorted()
+        .collect();
+    report.similar = find_similar(&by_password);
+
+    if json {
+        serde_json::to_writer_pretty(std::io::stdout(), &report)?;
+        println!();
+    } else {
+        print_report(&report, &args);
+    }
+    Ok(())
+}
+
+fn weak(key: &str, strength: Strength) -> Weak {
+    Weak {
+        key: key.to_string(),
+        score: strength.score,
+        entropy: (strength.entropy * 10.0).round() / 10.0,
+    }
+}
+
+fn last_modified(
+    store: &Path,
+    key: &str,
+    last_commits: &HashMap<String, SystemTime>,
+) -> Option<SystemTime> {
+    last_commits.get(key).copied().or_else(|| {
+        fs::metadata(store.join(key))
+            .and_then(|m| m.modified())
+            .ok()
+    })
+}
+
+fn find_similar(by_password: &HashMap<String, Vec<String>>) -> Vec<Vec<String>> {
+    let passwords: Vec<_> = by_password.keys().sorted().collect();
+    passwords
+        .iter()
+        .tuple_combinations()
+        .filter(|(a, b)| are_similar(a, b))
+        .map(|(a, b)| {
+            by_password[*a]
+                .iter()
+                .chain(by_password[*b].iter())
+                .cloned()
+                .collect()
+        })
+        .sorted()
+        .collect()
+}
+
+fn are_similar(a: &str, b: &str) -> bool {
+    let min_len = a.chars().count().min(b.chars().count());
+    edit_distance(&a.to_lowercase(), &b.to_lowercase()) * 4 <= min_len
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn print_report(report: &Report, args: &AuditArgs) {
+    let width = report
+        .weak
+        .iter()
+        .map(|w| w.key.len())
+        .chain(report.stale.iter().map(|s| s.key.len()))
+        .chain(report.missing_fields.iter().map(|m| m.key.len()))
+        .max()
+        .unwrap_or_default();
+
+    print_heading(&format!("Weak passwords ({})", report.weak.len()));
+    for weak in &report.weak {
+        let label = Strength {
+            entropy: weak.entropy,
+            score: weak.score,
+        }
+        .label();
+        println!(
+            "  {:width$}  {label} (score {}/{}, ~{:.0} bits)",
+            weak.key,
+            weak.score,
+            Strength::MAX_SCORE,
+            weak.entropy
+        );
+    }
+    print_heading(&format!("Reused passwords ({})", report.reused.len()));
+    for keys in &report.reused {
+        println!("  {}", keys.join(", "));
+    }
+    print_heading(&format!("Similar passwords ({})", report.similar.len()));
+    for keys in &report.similar {
+        println!("  {}", keys.join(" ~ "));
+    }
+    print_heading(&format!(
+        "Unchanged for more than {} days ({})",
+        args.max_age,
+        report.stale.len()
+    ));
+    for stale in &report.stale {
+        println!("  {:width$}  {} days", stale.key, stale.days);
+    }
+    if !args.required_fields.is_empty() {
+        print_heading(&format!("Missing fields ({})", report.missing_fields.len()));
+        for missing in &report.missing_fields {
+            println!("  {:width$}  {}", missing.key, missing.fields.join(", "));
+        }
+    }
+    if !report.unreadable.is_empty() {
+        print_heading(&format!("Could not decrypt ({})", report.unreadable.len()));
+        for key in &report.unreadable {
+            println!("  {key}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(0, edit_distance("same", "same"));
+        assert_eq!(1, edit_distance("hunter2", "hunter3"));
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+        assert_eq!(4, edit_distance("", "four"));
+    }
+
+    #[test]
+    fn test_find_similar() {
+        let by_password = HashMap::from([
+            ("Summer2024!".to_string(), vec!["a".to_string()]),
+            (
+                "Summer2025!".to_string(),
+                vec!["b".to_string(), "c".to_string()],
+            ),
+            ("x7#Lq!9vR@2mZp$W".to_string(), vec!["d".to_string()]),
+            ("SUMMER2024!".to_string(), vec!["e".to_string()]),
+        ]);
+        let similar = find_similar(&by_password);
+        assert_eq!(
+            vec![
+                vec!["e".to_string(), "a".to_string()],
+                vec!["e".to_string(), "b".to_string(), "c".to_string()],
+                vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            ]
+            .into_iter()
+            .sorted()
+            .collect::<Vec<_>>(),
+            similar
+        );
+    }
+}
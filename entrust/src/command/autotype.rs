@@ -1,10 +1,11 @@
 use crate::dialog;
+use crate::key::resolve_existing;
 use anyhow::anyhow;
 use clap::Args;
 use color_print::cstr;
 use const_format::formatcp;
 use enigo::{Direction, Enigo, Key, Keyboard, Settings};
-use entrust_core::{Backend, resolve_existing_location};
+use entrust_core::Backend;
 use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::Duration;
@@ -65,7 +66,7 @@ fn parse_segment(string: &str, store: &Path) -> anyhow::Result<Segment> {
             Ok(Segment::Pass(pass))
         }
         s => {
-            let key = resolve_existing_location(store, s, false)?;
+            let key = resolve_existing(store, s, false)?;
             let pass = Backend::decrypt(&key)?;
             Ok(Segment::Pass(pass))
         }
@@ -0,0 +1,69 @@
+use crate::bulk::decrypt_all;
+use anyhow::anyhow;
+use clap::Args;
+use color_print::cstr;
+use entrust_core::breach::BreachDatabase;
+use entrust_core::{Entry, get_existing_keys};
+use std::path::PathBuf;
+
+pub(super) const ABOUT: &str = "Check passwords against a local copy of Pwned Passwords";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Check passwords against a local copy of Pwned Passwords
+
+  Looks up the SHA-1 hash of every password below <bold,#ffb86c>DIR</> in the downloaded \
+  Pwned Passwords dataset (ordered by hash) without sending anything over the network. \
+  Fails if any password has been found"
+);
+
+pub(super) const BREACH_DB_ENV_VAR: &str = "ENT_BREACH_DB";
+
+#[derive(Args, Debug)]
+pub struct BreachCheckArgs {
+    /// The directory to check (default: the whole store)
+    dir: Option<String>,
+    /// The Pwned Passwords SHA-1 file ordered by hash, or an index created with --build-index
+    #[arg(long, env = BREACH_DB_ENV_VAR, value_name = "FILE")]
+    db: PathBuf,
+    /// Instead of checking, convert the database into a compact index written to the given file
+    #[arg(long, value_name = "FILE")]
+    build_index: Option<PathBuf>,
+}
+
+pub fn run(store: PathBuf, args: BreachCheckArgs) -> anyhow::Result<()> {
+    if let Some(index) = args.build_index {
+        let records = entrust_core::breach::build_index(&args.db, &index)?;
+        eprintln!("Wrote {records} hashes to {}", index.display());
+        return Ok(());
+    }
+    let mut database = BreachDatabase::open(&args.db)?;
+    let keys = get_existing_keys(&store, args.dir.as_deref())?;
+    let width = keys.iter().map(|k| k.len()).max().unwrap_or_default();
+    let mut compromised = 0;
+    for (key, decrypted) in keys.iter().zip(decrypt_all(&store, &keys)) {
+        let content = match decrypted {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("Could not decrypt {key}: {e}");
+                continue;
+            }
+        };
+        if let Some(count) = database.lookup(&Entry::parse(&content).password)? {
+            println!("  {key:width$}  seen {count} times in breaches");
+            compromised += 1;
+        }
+    }
+    if compromised > 0 {
+        return Err(anyhow!(
+            "{compromised} of {} passwords have been found in breaches",
+            keys.len()
+        ));
+    }
+    println!(
+        "None of {} passwords have been found in breaches",
+        keys.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use crate::bulk::{decrypt_all_or_abort, reencrypt_all};
+use crate::command::BackendValueEnum;
+use crate::key::key_of;
+use anyhow::anyhow;
+use clap::Args;
+use color_print::cstr;
+use entrust_core::{Backend, get_existing_keys, git};
+use itertools::Itertools;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+pub(super) const ABOUT: &str = "Convert entries between gpg and age";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Convert entries between gpg and age
+
+  Decrypts every entry below <bold,#ffb86c>DIR</> which is not yet encrypted with the backend \
+  given to <bold,#ffb86c>--to</> and re-encrypts it with that backend to the recipients in the nearest \
+  <bold,#ffb86c>.age-id</> or <bold,#ffb86c>.gpg-id</>. The recipient file of the store is created if needed.
+  If a directory has its own recipient file for the current backend but none for the new one, \
+  nothing is converted until a matching one is created there.
+  All entries are decrypted before the first one is changed, so an unreadable entry leaves the store untouched."
+);
+
+#[derive(Args, Debug)]
+pub struct ConvertArgs {
+    /// The directory to convert (default: the whole store)
+    dir: Option<String>,
+    /// The backend to convert the entries to
+    #[arg(long, value_enum)]
+    pub to: BackendValueEnum,
+    /// Do not commit the change to the git repository if one exists
+    #[arg(long = "no-git")]
+    no_git: bool,
+}
+
+pub fn run(store: PathBuf, args: ConvertArgs) -> anyhow::Result<()> {
+    let target = Backend::from(args.to);
+    let keys: Vec<_> = get_existing_keys(&store, args.dir.as_deref())?
+        .into_iter()
+        .filter(|key| Backend::detect(&store.join(key)).is_ok_and(|b| b != target))
+        .collect();
+    if keys.is_empty() {
+        eprintln!(
+            "All entries are already encrypted with {}",
+            target.display_name()
+        );
+        return Ok(());
+    }
+    let dirs = missing_recipient_files(&store, &keys, target)?;
+    if !dirs.is_empty() {
+        return Err(anyhow!(
+            "Create a {} in these directories first, as their entries would otherwise be encrypted \
+            to the recipients of a parent directory: {}",
+            target.recipient_file_name(),
+            dirs.into_iter().join(", ")
+        ));
+    }
+    let contents = decrypt_all_or_abort(&store, &keys)?;
+    reencrypt_all(&store, target, &keys, contents)?;
+    eprintln!(
+        "Converted {} entries to {}",
+        keys.len(),
+        target.display_name()
+    );
+
+    if !args.no_git {
+        git::convert(&store, &keys, target.display_name())?;
+    }
+    Ok(())
+}
+
+/// The directories whose recipient file applies to some of the entries,
+/// while the nearest recipient file of the target backend is in a parent directory or missing
+fn missing_recipient_files(
+    store: &Path,
+    keys: &[String],
+    target: Backend,
+) -> anyhow::Result<BTreeSet<String>> {
+    let mut dirs = BTreeSet::new();
+    for key in keys {
+        let location = store.join(key);
+        let Some(source_file) = Backend::detect(&location)?.recipient_file(store, &location) else {
+            continue;
+        };
+        let source_dir = source_file.parent().unwrap_or(store);
+        let shallower = match target.recipient_file(store, &location) {
+            Some(target_file) => {
+                let target_dir = target_file.parent().unwrap_or(store);
+                target_dir != source_dir && source_dir.starts_with(target_dir)
+            }
+            None => true,
+        };
+        if shallower {
+            let dir = key_of(store, source_dir)?;
+            dirs.insert(if dir.is_empty() { "/".to_string() } else { dir });
+        }
+    }
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entrust_core::write_recipients;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path();
+        fs::create_dir_all(store.join("work/team")).unwrap();
+        fs::create_dir_all(store.join("shared")).unwrap();
+        write_recipients(&store.join(".gpg-id"), &["me@example.com".to_string()]).unwrap();
+        write_recipients(&store.join(".age-id"), &["age1me".to_string()]).unwrap();
+        write_recipients(
+            &store.join("work/.gpg-id"),
+            &["work@example.com".to_string()],
+        )
+        .unwrap();
+        write_recipients(
+            &store.join("shared/.gpg-id"),
+            &["you@example.com".to_string()],
+        )
+        .unwrap();
+        write_recipients(&store.join("shared/.age-id"), &["age1you".to_string()]).unwrap();
+        for key in ["mail", "work/github", "work/team/jira", "shared/wifi"] {
+            fs::write(store.join(key), "-----BEGIN PGP MESSAGE-----\n").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_missing_recipient_files() {
+        let dir = setup();
+        let keys = get_existing_keys(dir.path(), None).unwrap();
+        let missing = missing_recipient_files(dir.path(), &keys, Backend::Age).unwrap();
+        assert_eq!(vec!["work"], missing.into_iter().collect::<Vec<_>>());
+
+        write_recipients(
+            &dir.path().join("work/team/.age-id"),
+            &["age1team".to_string()],
+        )
+        .unwrap();
+        let missing = missing_recipient_files(dir.path(), &keys, Backend::Age).unwrap();
+        assert_eq!(vec!["work"], missing.into_iter().collect::<Vec<_>>());
+
+        write_recipients(&dir.path().join("work/.age-id"), &["age1work".to_string()]).unwrap();
+        let missing = missing_recipient_files(dir.path(), &keys, Backend::Age).unwrap();
+        assert!(missing.is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use crate::bulk::decrypt_all_or_abort;
+use crate::key::{Key, key_of, resolve_existing};
+use crate::theme::chevron_prompt;
+use anyhow::anyhow;
+use clap::Args;
+use color_print::cstr;
+use entrust_core::{Backend, get_existing_keys, git, resolve_new_location};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(super) const ABOUT: &str = "Copy a password to another location in the store";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Copy a password to another location in the store
+
+  The copy is re-encrypted to the recipients in the nearest <bold,#ffb86c>.age-id</> or \
+  <bold,#ffb86c>.gpg-id</> of its new location, so it can be shared with different recipients \
+  than the original. The backend of the original is kept."
+);
+
+#[derive(Args, Debug)]
+pub struct CopyArgs {
+    /// The key of the entry or directory to copy
+    from: Option<String>,
+    /// The key of the copy
+    to: Option<String>,
+    /// Enable copying directories
+    #[arg(short, long)]
+    recurse: bool,
+    /// Do not commit the copy to the git repository if one exists
+    #[arg(long = "no-git")]
+    no_git: bool,
+}
+
+pub fn run(store: PathBuf, args: CopyArgs) -> anyhow::Result<()> {
+    let from = args.from.unwrap_or_select_existing(&store)?;
+    let from_location = resolve_existing(&store, &from, args.recurse)?;
+    let from = key_of(&store, &from_location)?;
+    if from.is_empty() {
+        return Err(anyhow!("Cannot copy the whole store"));
+    }
+    if from_location.is_dir() && !args.recurse {
+        return Err(anyhow!("{from} is a directory; specify --recurse to copy"));
+    }
+    let to = args
+        .to
+        .unwrap_or_read_new(chevron_prompt!("New key"), &store)?;
+    let to = to.trim_end_matches('/').to_string();
+
+    let from_keys = if from_location.is_dir() {
+        get_existing_keys(&store, Some(&from))?
+    } else {
+        vec![from.clone()]
+    };
+    let to_keys: Vec<_> = from_keys
+        .iter()
+        .map(|key| format!("{to}{}", &key[from.len()..]))
+        .collect();
+    let to_locations = to_keys
+        .iter()
+        .map(|key| resolve_new_location(&store, key))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let contents = decrypt_all_or_abort(&store, &from_keys)?;
+
+    let mut copied = Vec::with_capacity(to_locations.len());
+    for ((from_key, location), content) in from_keys.iter().zip(&to_locations).zip(contents) {
+        let result = copy(&store, &store.join(from_key), location, &content);
+        if let Err(err) = result {
+            for location in copied {
+                fs::remove_file(location)?;
+            }
+            return Err(err.context(format!("Could not copy {from_key}; nothing was copied")));
+        }
+        copied.push(location);
+    }
+    if from_location.is_dir() {
+        eprintln!("Copied {} entries", to_keys.len());
+    }
+
+    if !args.no_git {
+        git::copy(&store, &from, &to, &to_keys)?;
+    }
+    Ok(())
+}
+
+fn copy(store: &Path, from: &Path, to: &Path, content: &str) -> anyhow::Result<()> {
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Backend::detect(from)?.encrypt(content.as_bytes(), store, to)
+}
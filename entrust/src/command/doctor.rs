@@ -0,0 +1,524 @@
+use crate::key::key_of;
+use crate::theme::{color, print_heading};
+use anyhow::anyhow;
+use color_print::{cformat, cstr};
+use entrust_core::age::identity::{identity_file, recipients_of};
+use entrust_core::{Backend, get_existing_keys, git, read_recipients};
+use itertools::Itertools;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+pub(super) const ABOUT: &str = "Check the environment for common problems";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Check the environment for common problems
+
+  Checks the <bold,#ffb86c>age</> and <bold,#ffb86c>gpg</> binaries, the recipient files, \
+  the identity in <bold,#ffb86c>AGE_IDENTITY</>, the agent, the permissions of the store \
+  and the state of its git repository, and suggests fixes for the problems found.
+  Fails if any check fails; warnings do not."
+);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Level {
+    Info,
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug)]
+struct Check {
+    level: Level,
+    message: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn new(level: Level, message: impl Into<String>) -> Check {
+        Check {
+            level,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: impl Into<String>) -> Check {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+pub fn run(store: PathBuf) -> anyhow::Result<()> {
+    let sections = [
+        ("Backends", check_backends(&store)),
+        ("Recipients", check_recipients(&store)),
+        ("Identity", check_identity(&store)),
+        ("Agent", check_agent()),
+        ("Permissions", check_permissions(&store)),
+        ("Git", check_git(&store)),
+    ];
+    let mut errors = 0;
+    for (title, checks) in &sections {
+        print_heading(title);
+        for check in checks {
+            print_check(check);
+            errors += usize::from(check.level == Level::Error);
+        }
+    }
+    match errors {
+        0 => Ok(()),
+        1 => Err(anyhow!("1 check failed")),
+        n => Err(anyhow!("{n} checks failed")),
+    }
+}
+
+fn check_backends(store: &Path) -> Vec<Check> {
+    [Backend::Age, Backend::Gpg]
+        .into_iter()
+        .map(|backend| {
+            let name = backend.display_name();
+            match backend.version() {
+                Ok(version) => Check::new(Level::Ok, format!("{name}: {version}")),
+                Err(err) => {
+                    let used = backend
+                        .recipient_files(store)
+                        .is_ok_and(|files| !files.is_empty());
+                    let level = if used { Level::Error } else { Level::Warning };
+                    let not_found = err
+                        .downcast_ref::<io::Error>()
+                        .is_some_and(|e| e.kind() == io::ErrorKind::NotFound);
+                    let message = if not_found {
+                        format!("{name} was not found")
+                    } else {
+                        format!("{name} could not be run: {err}")
+                    };
+                    Check::new(level, message)
+                        .with_fix(format!("Install {name} and make sure it is in PATH"))
+                }
+            }
+        })
+        .collect()
+}
+
+fn check_recipients(store: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for backend in [Backend::Age, Backend::Gpg] {
+        let files = match backend.recipient_files(store) {
+            Ok(files) => files,
+            Err(err) => {
+                checks.push(Check::new(
+                    Level::Error,
+                    format!(
+                        "The {} files could not be listed: {err}",
+                        backend.recipient_file_name()
+                    ),
+                ));
+                continue;
+            }
+        };
+        for path in files {
+            let file = display_key(store, &path);
+            let recipients = match read_recipients(&path) {
+                Ok(recipients) => recipients,
+                Err(err) => {
+                    checks.push(
+                        Check::new(Level::Error, format!("{file} could not be read: {err}"))
+                            .with_fix(format!(
+                                "Write one {} recipient per line into {file}",
+                                backend.display_name()
+                            )),
+                    );
+                    continue;
+                }
+            };
+            for recipient in recipients {
+                checks.push(match backend.check_recipient(&recipient) {
+                    Ok(()) => Check::new(Level::Ok, format!("{file}: {recipient}")),
+                    Err(err) => {
+                        Check::new(Level::Error, format!("{file}: {err}")).with_fix(match backend {
+                            Backend::Age => "Use a recipient as printed by age-keygen -y",
+                            Backend::Gpg => "Import the public key with gpg --import",
+                        })
+                    }
+                });
+            }
+        }
+    }
+    if checks.is_empty() {
+        checks.push(
+            Check::new(Level::Error, "The store has no recipient file")
+                .with_fix("Run ent add, which asks for a recipient and creates the file"),
+        );
+    }
+    checks
+}
+
+fn check_identity(store: &Path) -> Vec<Check> {
+    let recipient_files: Vec<_> = Backend::Age
+        .recipient_files(store)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|file| Some((display_key(store, &file), read_recipients(&file).ok()?)))
+        .collect();
+    if recipient_files.is_empty() {
+        return vec![Check::new(
+            Level::Info,
+            "The store has no age recipient; no identity needed",
+        )];
+    }
+    let Some(identity_file) = identity_file() else {
+        return vec![
+            Check::new(Level::Warning, "AGE_IDENTITY is not set").with_fix(
+                "Set AGE_IDENTITY to the path of your identity file or pipe the identity into stdin",
+            ),
+        ];
+    };
+    let path = Path::new(&identity_file);
+    let identity = match fs::read(path) {
+        Ok(identity) => identity,
+        Err(err) => {
+            return vec![
+                Check::new(
+                    Level::Error,
+                    format!("{identity_file} could not be read: {err}"),
+                )
+                .with_fix("Point AGE_IDENTITY to a readable identity file"),
+            ];
+        }
+    };
+    if matches!(Backend::detect(path), Ok(Backend::Age)) {
+        return vec![Check::new(
+            Level::Info,
+            format!("{identity_file} is encrypted; not checked against .age-id"),
+        )];
+    }
+    let mut checks = match recipients_of(&identity) {
+        Ok(identity_recipients) => {
+            let (matching, other): (Vec<_>, Vec<_>) =
+                recipient_files.iter().partition(|(_, recipients)| {
+                    recipients.iter().any(|r| identity_recipients.contains(r))
+                });
+            let files = |files: Vec<&(String, Vec<String>)>| {
+                files.into_iter().map(|(file, _)| file.as_str()).join(", ")
+            };
+            if matching.is_empty() {
+                vec![
+                    Check::new(
+                        Level::Error,
+                        format!("{identity_file} matches no recipient in any .age-id"),
+                    )
+                    .with_fix("Point AGE_IDENTITY to the identity of a recipient in .age-id"),
+                ]
+            } else {
+                let mut checks = vec![Check::new(
+                    Level::Ok,
+                    format!("{identity_file} matches a recipient in {}", files(matching)),
+                )];
+                if !other.is_empty() {
+                    checks.push(Check::new(
+                        Level::Info,
+                        format!("{identity_file} matches no recipient in {}", files(other)),
+                    ));
+                }
+                checks
+            }
+        }
+        Err(err) => vec![
+            Check::new(
+                Level::Warning,
+                format!("The recipient of {identity_file} could not be derived: {err}"),
+            )
+            .with_fix("Make sure age-keygen is installed and the file contains an AGE-SECRET-KEY"),
+        ],
+    };
+    #[cfg(unix)]
+    if mode(path).is_some_and(|mode| mode & 0o077 != 0) {
+        checks.push(
+            Check::new(
+                Level::Warning,
+                format!("{identity_file} is accessible by other users"),
+            )
+            .with_fix(format!("chmod 600 {identity_file}")),
+        );
+    }
+    checks
+}
+
+#[cfg(feature = "agent")]
+fn check_agent() -> Vec<Check> {
+    let socket = entrust_agent::env::agent_socket_name();
+    if entrust_agent::client::is_server_running() {
+        return vec![Check::new(Level::Ok, format!("Running on socket {socket}"))];
+    }
+    let mut checks = vec![Check::new(
+        Level::Info,
+        format!("Not running on socket {socket}; it is started by the next age decryption"),
+    )];
+    let bin = entrust_agent::env::agent_bin();
+    if find_binary(&bin).is_none() {
+        checks.push(
+            Check::new(Level::Warning, format!("{bin} was not found"))
+                .with_fix("Install ent-agent or set ENT_AGENT_BIN to its path"),
+        );
+    }
+    checks
+}
+
+#[cfg(not(feature = "agent"))]
+fn check_agent() -> Vec<Check> {
+    vec![Check::new(
+        Level::Info,
+        "Built without the agent feature; the identity is read for every decryption",
+    )]
+}
+
+#[cfg(feature = "agent")]
+fn find_binary(bin: &str) -> Option<PathBuf> {
+    let path = Path::new(bin);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .flat_map(|dir| [dir.join(bin), dir.join(format!("{bin}.exe"))])
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(unix)]
+fn check_permissions(store: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if mode(store).is_some_and(|mode| mode & 0o077 != 0) {
+        checks.push(
+            Check::new(Level::Warning, "The store is accessible by other users")
+                .with_fix(format!("chmod 700 {}", store.display())),
+        );
+    }
+    let writable: Vec<_> = get_existing_keys(store, None)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|key| mode(&store.join(key)).is_some_and(|mode| mode & 0o022 != 0))
+        .collect();
+    if !writable.is_empty() {
+        checks.push(
+            Check::new(
+                Level::Warning,
+                format!(
+                    "{} entries are writable by other users: {}",
+                    writable.len(),
+                    writable.join(", ")
+                ),
+            )
+            .with_fix(format!("chmod -R go-w {}", store.display())),
+        );
+    }
+    if checks.is_empty() {
+        checks.push(Check::new(
+            Level::Ok,
+            "The store is only accessible by its owner",
+        ));
+    }
+    checks
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_store: &Path) -> Vec<Check> {
+    vec![Check::new(Level::Info, "Not checked on this platform")]
+}
+
+#[cfg(unix)]
+fn mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).ok().map(|m| m.permissions().mode())
+}
+
+fn check_git(store: &Path) -> Vec<Check> {
+    let status = match git::status(store) {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            return vec![
+                Check::new(Level::Info, "The store is not a git repository")
+                    .with_fix("Run ent git init to keep a history of the store"),
+            ];
+        }
+        Err(err) => {
+            return vec![
+                Check::new(Level::Error, format!("git status failed: {err}"))
+                    .with_fix("Install git and check the repository with ent git status"),
+            ];
+        }
+    };
+    let mut checks = Vec::new();
+    let untracked = untracked_entries(&status);
+    if !untracked.is_empty() {
+        checks.push(
+            Check::new(
+                Level::Warning,
+                format!("Untracked entries: {}", untracked.join(", ")),
+            )
+            .with_fix("Run ent git add -A and ent git commit"),
+        );
+    }
+    if !status.uncommitted.is_empty() {
+        checks.push(
+            Check::new(
+                Level::Warning,
+                format!("Uncommitted changes: {}", status.uncommitted.join(", ")),
+            )
+            .with_fix("Review them with ent git diff and commit or restore them"),
+        );
+    }
+    if checks.is_empty() {
+        checks.push(Check::new(Level::Ok, "All entries are committed"));
+    }
+    checks
+}
+
+/// The untracked files of the status which are entries, leaving out those in hidden directories
+/// like the trash and hidden files like recipient files
+fn untracked_entries(status: &git::Status) -> Vec<String> {
+    status
+        .untracked
+        .iter()
+        .filter(|file| !file.split('/').any(|segment| segment.starts_with('.')))
+        .cloned()
+        .collect()
+}
+
+/// The key of a file in the store, or its path if it is outside of it
+fn display_key(store: &Path, file: &Path) -> String {
+    key_of(store, file).unwrap_or_else(|_| file.display().to_string())
+}
+
+fn print_check(check: &Check) {
+    let (symbol, message) = (symbol(check.level), &check.message);
+    if color() {
+        let symbol = match check.level {
+            Level::Info => cformat!("<bright-black>{symbol}</>"),
+            Level::Ok => cformat!("<green>{symbol}</>"),
+            Level::Warning => cformat!("<yellow>{symbol}</>"),
+            Level::Error => cformat!("<red>{symbol}</>"),
+        };
+        println!("  {symbol} {message}");
+        if let Some(fix) = &check.fix {
+            println!("{}", cformat!("    <bright-black>fix: {fix}</>"));
+        }
+    } else {
+        println!("  {symbol} {message}");
+        if let Some(fix) = &check.fix {
+            println!("    fix: {fix}");
+        }
+    }
+}
+
+fn symbol(level: Level) -> char {
+    match level {
+        Level::Info => '-',
+        Level::Ok => '✔',
+        Level::Warning => '!',
+        Level::Error => '✘',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entrust_core::write_recipients;
+    use tempfile::TempDir;
+
+    const AGE_RECIPIENT: &str = "age1qyqszqgpqyqszqgpqyqszqgpqyqszqgpqyqszqgpqyqszqgpqyqs3290gq";
+
+    fn messages(checks: &[Check], level: Level) -> Vec<&str> {
+        checks
+            .iter()
+            .filter(|check| check.level == level)
+            .map(|check| check.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_check_nested_recipients() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path();
+        fs::create_dir_all(store.join("work")).unwrap();
+        fs::create_dir_all(store.join("shared")).unwrap();
+        write_recipients(&store.join("work/.age-id"), &[AGE_RECIPIENT.to_string()]).unwrap();
+        write_recipients(&store.join("shared/.age-id"), &["age1invalid".to_string()]).unwrap();
+        let checks = check_recipients(store);
+        assert_eq!(
+            vec![format!("work/.age-id: {AGE_RECIPIENT}")],
+            messages(&checks, Level::Ok)
+        );
+        let errors = messages(&checks, Level::Error);
+        assert_eq!(1, errors.len());
+        assert!(errors[0].starts_with("shared/.age-id: "));
+    }
+
+    #[test]
+    fn test_check_no_recipients() {
+        let dir = tempfile::tempdir().unwrap();
+        let checks = check_recipients(dir.path());
+        assert_eq!(
+            vec!["The store has no recipient file"],
+            messages(&checks, Level::Error)
+        );
+        assert_eq!(Level::Info, check_identity(dir.path())[0].level);
+    }
+
+    #[test]
+    fn test_untracked_entries() {
+        let status = git::Status {
+            untracked: vec![
+                "mail".to_string(),
+                ".trash/1700000000/work/github".to_string(),
+                "work/.age-id".to_string(),
+                "work/github".to_string(),
+            ],
+            uncommitted: Vec::new(),
+        };
+        assert_eq!(vec!["mail", "work/github"], untracked_entries(&status));
+    }
+
+    #[cfg(unix)]
+    fn set_mode(path: &Path, mode: u32) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[cfg(unix)]
+    fn setup_permissions() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("work")).unwrap();
+        fs::write(dir.path().join("mail"), "").unwrap();
+        fs::write(dir.path().join("work/github"), "").unwrap();
+        set_mode(dir.path(), 0o700);
+        set_mode(&dir.path().join("mail"), 0o600);
+        set_mode(&dir.path().join("work/github"), 0o600);
+        dir
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_permissions() {
+        let dir = setup_permissions();
+        let checks = check_permissions(dir.path());
+        assert_eq!(
+            vec!["The store is only accessible by its owner"],
+            messages(&checks, Level::Ok)
+        );
+        assert!(messages(&checks, Level::Warning).is_empty());
+
+        set_mode(dir.path(), 0o755);
+        set_mode(&dir.path().join("work/github"), 0o620);
+        let checks = check_permissions(dir.path());
+        assert_eq!(
+            vec![
+                "The store is accessible by other users",
+                "1 entries are writable by other users: work/github",
+            ],
+            messages(&checks, Level::Warning)
+        );
+        assert!(messages(&checks, Level::Ok).is_empty());
+    }
+}
@@ -1,5 +1,4 @@
 use std::borrow::Cow;
-use std::fs;
 use std::io::{IsTerminal, Read, stdin};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
@@ -9,8 +8,9 @@ use color_print::cstr;
 
 use crate::command::BackendValueEnum;
 use crate::dialog::read_password_interactive;
-use crate::key::Key;
-use entrust_core::{Backend, git, resolve_existing_location};
+use crate::key::{Key, key_of, resolve_existing};
+use crate::strength::{MIN_STRENGTH_ENV_VAR, check_strength};
+use entrust_core::{Backend, git};
 
 pub(super) const ABOUT: &str = "Change an existing password";
 
@@ -35,40 +35,39 @@ pub struct EditArgs {
     /// Choose gpg or age for re-encryption
     #[arg(short, long, value_enum, default_value_t = BackendValueEnum::Age)]
     pub backend: BackendValueEnum,
+    /// Reject passwords with an estimated strength score (0-4) below the given value
+    #[arg(long, env = MIN_STRENGTH_ENV_VAR, value_name = "SCORE",
+    value_parser = clap::value_parser!(u8).range(0..=4))]
+    min_strength: Option<u8>,
 }
 
 pub fn run(store: PathBuf, args: EditArgs) -> anyhow::Result<()> {
     let key = &args.key.unwrap_or_select_existing(&store)?;
-    let location = resolve_existing_location(&store, key, false)?;
+    let location = resolve_existing(&store, key, false)?;
+    let key = &key_of(&store, &location)?;
 
     let edited = if stdin().is_terminal() {
-        edit_interactive(args.cleartext, &location)
+        edit_interactive(args.cleartext, &location, args.min_strength)
     } else {
         edit_non_interactive()
     }?;
+    check_strength(&edited, args.min_strength)?;
 
-    let mut bak = location.clone();
-    bak.as_mut_os_string().push(".bak");
-    fs::rename(&location, &bak)?;
-
-    let encryption_result =
-        Backend::from(args.backend).encrypt(edited.as_bytes(), &store, &location);
-    if encryption_result.is_ok() {
-        git::edit(&store, key)?;
-        fs::remove_file(bak)?;
-    } else {
-        fs::rename(&bak, &location)?;
-    }
-    encryption_result
+    Backend::from(args.backend).encrypt_replacing(edited.as_bytes(), &store, &location)?;
+    git::edit(&store, key)
 }
 
-fn edit_interactive(cleartext: bool, bak: &Path) -> anyhow::Result<String> {
+fn edit_interactive(
+    cleartext: bool,
+    bak: &Path,
+    min_strength: Option<u8>,
+) -> anyhow::Result<String> {
     let initial = if cleartext {
         Cow::Owned(Backend::decrypt(bak)?)
     } else {
         Cow::Borrowed("")
     };
-    read_password_interactive(initial.deref())
+    read_password_interactive(initial.deref(), min_strength)
 }
 
 fn edit_non_interactive() -> anyhow::Result<String> {
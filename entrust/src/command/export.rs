@@ -0,0 +1,222 @@
+use crate::bulk::decrypt_all;
+use crate::theme::DIALOG_THEME;
+use anyhow::{Context, anyhow};
+use clap::{ArgGroup, Args, ValueEnum};
+use color_print::cstr;
+use entrust_core::archive::{ArchiveEntry, Protection};
+use entrust_core::{Backend, Entry, get_existing_keys};
+use entrust_dialog::dialog::Dialog;
+use entrust_dialog::yes_no::YesNoDialog;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::IsTerminal;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::{io, time};
+
+pub(super) const ABOUT: &str = "Export decrypted entries";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Export decrypted entries
+
+  With <bold,#ffb86c>--archive</>, writes the entries into a tar archive which is encrypted \
+  with <bold,#ffb86c>age</> to the given recipient or a passphrase.
+  With <bold,#ffb86c>--format</>, prints the entries in plaintext to stdout"
+);
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("output").required(true)))]
+pub struct ExportArgs {
+    /// The directory to export (default: the whole store)
+    dir: Option<String>,
+    /// The file to write the encrypted archive to
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        group = "output",
+        requires = "protection"
+    )]
+    archive: Option<PathBuf>,
+    /// The age recipient to encrypt the archive to
+    #[arg(short, long, group = "protection", requires = "archive")]
+    recipient: Option<String>,
+    /// Encrypt the archive with a passphrase instead of a recipient
+    #[arg(short, long, group = "protection", requires = "archive")]
+    passphrase: bool,
+    /// Print the decrypted entries to stdout in the given format
+    #[arg(short, long, value_enum, group = "output")]
+    format: Option<Format>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Json,
+    Yaml,
+}
+
+/// A decrypted entry; fields are kept in order as `[name, value]` pairs,
+/// so that repeated names and fields named like `password` or `notes` survive a round trip
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct Record {
+    pub(super) key: String,
+    pub(super) password: String,
+    #[serde(default)]
+    pub(super) fields: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(super) notes: String,
+    #[serde(default)]
+    pub(super) backend: Option<String>,
+    #[serde(default)]
+    pub(super) modified: Option<String>,
+}
+
+pub fn run(store: PathBuf, args: ExportArgs) -> anyhow::Result<()> {
+    let keys = get_existing_keys(&store, args.dir.as_deref())?;
+    match (args.archive, args.format) {
+        (Some(archive), _) => {
+            let protection = match args.recipient.as_deref() {
+                Some(recipient) => Protection::Recipient(recipient),
+                None => Protection::Passphrase,
+            };
+            export_archive(&store, &keys, &archive, protection)
+        }
+        (None, Some(format)) => export_plaintext(&store, &keys, format),
+        (None, None) => Ok(()),
+    }
+}
+
+fn export_archive(
+    store: &Path,
+    keys: &[String],
+    archive: &Path,
+    protection: Protection,
+) -> anyhow::Result<()> {
+    if archive.exists() {
+        return Err(anyhow!("{} already exists", archive.display()));
+    }
+    let mut entries = Vec::with_capacity(keys.len());
+    for (key, decrypted) in keys.iter().zip(decrypt_all(store, keys)) {
+        let content = decrypted.with_context(|| format!("Could not decrypt {key}"))?;
+        entries.push(ArchiveEntry {
+            key: key.clone(),
+            content,
+            modified: modified(store, key),
+        });
+    }
+    entrust_core::archive::write(archive, &entries, protection)?;
+    eprintln!(
+        "Exported {} entries to {}",
+        entries.len(),
+        archive.display()
+    );
+    Ok(())
+}
+
+fn export_plaintext(store: &Path, keys: &[String], format: Format) -> anyhow::Result<()> {
+    if io::stdout().is_terminal() && !confirm_plaintext(keys.len())? {
+        return Ok(());
+    }
+    let mut records = Vec::with_capacity(keys.len());
+    for (key, decrypted) in keys.iter().zip(decrypt_all(store, keys)) {
+        let content = decrypted.with_context(|| format!("Could not decrypt {key}"))?;
+        records.push(record(store, key, &content)?);
+    }
+    match format {
+        Format::Json => {
+            serde_json::to_writer_pretty(io::stdout(), &records)?;
+            println!();
+        }
+        Format::Yaml => serde_norway::to_writer(io::stdout(), &records)?,
+    }
+    Ok(())
+}
+
+/// The record of a decrypted entry, as exported and printed by `get --json`
+pub(super) fn record(store: &Path, key: &str, content: &str) -> anyhow::Result<Record> {
+    Ok(Record {
+        backend: Some(
+            Backend::detect(&store.join(key))?
+                .display_name()
+                .to_string(),
+        ),
+        modified: modified(store, key).map(|m| humantime::format_rfc3339_seconds(m).to_string()),
+        ..Record::new(key, content)
+    })
+}
+
+fn confirm_plaintext(count: usize) -> anyhow::Result<bool> {
+    let confirmed = YesNoDialog::default()
+        .with_message(format!(
+            "WARNING: This prints {count} decrypted entries in PLAINTEXT to the terminal. Continue?"
+        ))
+        .with_theme(DIALOG_THEME.deref())
+        .run()?;
+    Ok(confirmed)
+}
+
+fn modified(store: &Path, key: &str) -> Option<SystemTime> {
+    fs::metadata(store.join(key))
+        .and_then(|m| m.modified())
+        .ok()
+        .filter(|m| *m > time::UNIX_EPOCH)
+}
+
+impl Record {
+    fn new(key: &str, content: &str) -> Record {
+        let entry = Entry::parse(content);
+        Record {
+            key: key.to_string(),
+            password: entry.password,
+            fields: entry.fields,
+            notes: entry.notes.join("\n"),
+            backend: None,
+            modified: None,
+        }
+    }
+
+    pub(super) fn into_entry(self) -> Entry {
+        Entry {
+            password: self.password,
+            fields: self.fields,
+            notes: self.notes.lines().map(str::to_string).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(content: &str) -> String {
+        let record = Record::new("key", content);
+        let json = serde_json::to_string(&record).unwrap();
+        serde_json::from_str::<Record>(&json)
+            .unwrap()
+            .into_entry()
+            .to_string()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let content =
+            "secret\nuser: me\npassword: old\nnotes: field\nuser: other\nfirst note\nsecond note";
+        assert_eq!(content, roundtrip(content));
+        assert_eq!("secret", roundtrip("secret"));
+    }
+
+    #[test]
+    fn test_record_json() {
+        let record = Record::new("key", "secret\nuser: me\nuser: you\nnote");
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!("secret", json["password"]);
+        assert_eq!(
+            serde_json::json!([["user", "me"], ["user", "you"]]),
+            json["fields"]
+        );
+        assert_eq!("note", json["notes"]);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::theme::color;
+use clap::Args;
+use color_print::{cformat, cstr};
+use entrust_core::{KeyError, get_existing_keys};
+use entrust_dialog::select::filter::{FuzzyMatch, rank};
+use serde::Serialize;
+use std::path::PathBuf;
+
+pub(super) const ABOUT: &str = "Find keys matching a fuzzy query";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Find keys matching a fuzzy query
+
+  Ranks the keys of the store like the interactive selection does, best match first. \
+  With <bold,#ffb86c>--first</>, prints only the best match, so that scripts can resolve \
+  abbreviated keys deterministically. Fails if no key matches."
+);
+
+#[derive(Args, Debug)]
+pub struct FindArgs {
+    /// The query to match keys against
+    query: String,
+    /// Print only the best matching key
+    #[arg(long)]
+    first: bool,
+}
+
+#[derive(Serialize, Debug)]
+struct Found<'a> {
+    key: &'a str,
+    score: i64,
+}
+
+pub fn run(store: PathBuf, args: FindArgs, json: bool) -> anyhow::Result<()> {
+    let keys = get_existing_keys(&store, None)?;
+    let mut matches = rank(&keys, &args.query);
+    if matches.is_empty() {
+        return Err(KeyError::NotFound(args.query).into());
+    }
+    if args.first {
+        matches.truncate(1);
+    }
+    if json {
+        let found: Vec<_> = matches
+            .iter()
+            .map(|m| Found {
+                key: m.content,
+                score: m.score,
+            })
+            .collect();
+        if args.first {
+            serde_json::to_writer_pretty(std::io::stdout(), &found[0])?;
+        } else {
+            serde_json::to_writer_pretty(std::io::stdout(), &found)?;
+        }
+        println!();
+    } else {
+        for m in &matches {
+            println!("{}", highlight(m));
+        }
+    }
+    Ok(())
+}
+
+fn highlight(m: &FuzzyMatch) -> String {
+    if !color() {
+        return m.content.to_string();
+    }
+    m.content
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if m.matching_chars.contains(&i) {
+                cformat!("<bold,#ffb86c>{c}</>")
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
@@ -1,19 +1,27 @@
+use anyhow::anyhow;
 use clap::{Args, ValueEnum};
-use entrust_core::{Backend, generate_passphrase, generate_password};
+use entrust_core::breach::BreachDatabase;
+use entrust_core::{
+    Backend, BuiltinWordlist, Capitalization, GeneratorProfile, GeneratorType, Placement,
+    StoreConfig, Strength,
+};
 use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
-use std::{fs, io};
+use std::{env, fs, io};
 
 use crate::animation::animate;
+use crate::command::breach_check::BREACH_DB_ENV_VAR;
 use crate::command::{BackendValueEnum, clip};
+use crate::strength::print_strength;
 use entrust_core::git;
+use serde::Serialize;
 
 pub(crate) const ABOUT: &str = "Generate a random password";
 
 #[derive(Args, Debug)]
 pub struct GenerateArgs {
-    #[arg(default_value = "phrase")]
-    pub(super) r#type: Type,
+    /// The type of password to generate (default: phrase)
+    r#type: Option<Type>,
     /// Copy the generated password to the clipboard
     #[arg(short, long)]
     pub(super) clipboard: bool,
@@ -24,12 +32,14 @@ pub struct GenerateArgs {
     /// Encrypt and store the generated password under the given key
     #[arg(short, long, value_name = "KEY")]
     pub(super) store: Option<String>,
-    /// Length of the password (default: 7 words for type phrase; 20 characters for type word)
+    /// Use the given generator profile of the store
+    /// (default: the profile mapped to the key given with --store)
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+    /// Length of the password (default: 7 words for type phrase; 20 characters for type word;
+    /// 6 digits for type pin; 16 characters for type pronounceable; 32 characters for types hex and base64)
     #[arg(short, long)]
     length: Option<u8>,
-    /// Word separator for type phrase
-    #[arg(long = "sep", default_value = " ")]
-    pub(super) separator: String,
     /// Choose gpg or age for en-/decryption
     #[arg(short, long, value_enum, default_value_t = BackendValueEnum::Age)]
     pub(super) backend: BackendValueEnum,
@@ -39,22 +49,177 @@ pub struct GenerateArgs {
     /// Do not add the file to the git repository if one exists (only effective with --store)
     #[arg(long)]
     pub(super) no_git: bool,
+    /// Generate a new password while the generated one is found in the given Pwned Passwords file
+    #[arg(long, env = BREACH_DB_ENV_VAR, value_name = "FILE")]
+    pub(super) breach_db: Option<PathBuf>,
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+    #[command(flatten)]
+    policy: PolicyArgs,
 }
 
-#[derive(ValueEnum, Clone, Debug)]
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Passphrase options for type phrase")]
+struct PassphraseArgs {
+    /// Word separator (default: " ")
+    #[arg(long = "sep")]
+    separator: Option<String>,
+    /// Wordlist: eff-large, eff-short, eff-short-prefix or a file with one word per line
+    /// (default: the wordlist configured in the store or eff-large)
+    #[arg(long, value_name = "NAME|FILE")]
+    wordlist: Option<String>,
+    /// Capitalize the first letter of each word or of one random word (default: none)
+    #[arg(long, value_enum)]
+    capitalize: Option<CapitalizationValueEnum>,
+    /// Insert the given number of random digits
+    #[arg(long, value_name = "N")]
+    digits: Option<u8>,
+    /// Insert the given number of random symbols
+    #[arg(long, value_name = "N")]
+    symbols: Option<u8>,
+    /// Insert digits and symbols before or after a word, or inside a word (default: between)
+    #[arg(long, value_enum)]
+    insert: Option<PlacementValueEnum>,
+    /// Only use words short enough for the passphrase to have at most the given number of characters
+    #[arg(long, value_name = "N")]
+    max_length: Option<usize>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CapitalizationValueEnum {
+    None,
+    Each,
+    One,
+}
+
+impl From<CapitalizationValueEnum> for Capitalization {
+    fn from(value: CapitalizationValueEnum) -> Self {
+        match value {
+            CapitalizationValueEnum::None => Capitalization::None,
+            CapitalizationValueEnum::Each => Capitalization::Each,
+            CapitalizationValueEnum::One => Capitalization::One,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum PlacementValueEnum {
+    Between,
+    Within,
+}
+
+impl From<PlacementValueEnum> for Placement {
+    fn from(value: PlacementValueEnum) -> Self {
+        match value {
+            PlacementValueEnum::Between => Placement::Between,
+            PlacementValueEnum::Within => Placement::Within,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Character options for type word")]
+struct PolicyArgs {
+    /// Do not use lowercase letters (=false to override the profile)
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    no_lower: Option<bool>,
+    /// Do not use uppercase letters (=false to override the profile)
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    no_upper: Option<bool>,
+    /// Do not use digits (=false to override the profile)
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    no_digits: Option<bool>,
+    /// Do not use symbols (=false to override the profile)
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    no_symbols: Option<bool>,
+    /// Additional characters to use
+    #[arg(long, value_name = "CHARS")]
+    custom: Option<String>,
+    /// Do not use characters which are easily confused, like 0, O, 1 and l (=false to override the profile)
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    exclude_ambiguous: Option<bool>,
+    /// Do not use any of the given characters
+    #[arg(long, value_name = "CHARS")]
+    exclude: Option<String>,
+    /// Use at least the given number of lowercase letters
+    #[arg(long, value_name = "N")]
+    min_lower: Option<u8>,
+    /// Use at least the given number of uppercase letters
+    #[arg(long, value_name = "N")]
+    min_upper: Option<u8>,
+    /// Use at least the given number of digits
+    #[arg(long, value_name = "N")]
+    min_digits: Option<u8>,
+    /// Use at least the given number of symbols
+    #[arg(long, value_name = "N")]
+    min_symbols: Option<u8>,
+    /// Use at least the given number of the characters given with --custom
+    #[arg(long, value_name = "N", requires = "custom")]
+    min_custom: Option<u8>,
+}
+
+const MAX_BREACH_ATTEMPTS: usize = 10;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
 pub(super) enum Type {
+    /// Words from a wordlist
     Phrase,
+    /// Random characters
     Word,
+    /// Random digits, excluding trivial sequences like 1234
+    Pin,
+    /// Alternating consonants and vowels
+    Pronounceable,
+    /// Lowercase hexadecimal digits
+    Hex,
+    /// URL-safe base64 characters
+    Base64,
 }
 
-impl GenerateArgs {
-    pub(super) fn length(&self) -> u8 {
-        self.length.unwrap_or(match self.r#type {
-            Type::Phrase => 7,
-            Type::Word => 20,
-        })
+impl From<Type> for GeneratorType {
+    fn from(value: Type) -> Self {
+        match value {
+            Type::Phrase => GeneratorType::Phrase,
+            Type::Word => GeneratorType::Word,
+            Type::Pin => GeneratorType::Pin,
+            Type::Pronounceable => GeneratorType::Pronounceable,
+            Type::Hex => GeneratorType::Hex,
+            Type::Base64 => GeneratorType::Base64,
+        }
     }
+}
 
+impl GenerateArgs {
     pub(crate) fn needs_backend(&self) -> Option<Backend> {
         if self.store.is_some() {
             Some(self.backend.into())
@@ -62,19 +227,101 @@ impl GenerateArgs {
             None
         }
     }
+
+    /// The options given on the command line, falling back to the applicable profile of the store
+    fn profile(&self, store: &Path) -> anyhow::Result<GeneratorProfile> {
+        let config = StoreConfig::load(store)?;
+        let fallback = match (&self.profile, &self.store) {
+            (Some(profile), _) => config.profile(profile)?,
+            (None, Some(key)) => config.profile_for_key(key)?,
+            (None, None) => config.default_profile(),
+        };
+        let wordlist = match &self.passphrase.wordlist {
+            Some(name) if BuiltinWordlist::from_name(name).is_none() => Some(
+                env::current_dir()?
+                    .join(name)
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            wordlist => wordlist.clone(),
+        };
+        let passphrase = &self.passphrase;
+        let policy = &self.policy;
+        let cli = GeneratorProfile {
+            r#type: self.r#type.map(Into::into),
+            length: self.length,
+            wordlist,
+            sep: passphrase.separator.clone(),
+            capitalize: passphrase.capitalize.map(Into::into),
+            digits: passphrase.digits,
+            symbols: passphrase.symbols,
+            insert: passphrase.insert.map(Into::into),
+            max_length: passphrase.max_length,
+            no_lower: policy.no_lower,
+            no_upper: policy.no_upper,
+            no_digits: policy.no_digits,
+            no_symbols: policy.no_symbols,
+            custom: policy.custom.clone(),
+            exclude_ambiguous: policy.exclude_ambiguous,
+            exclude: policy.exclude.clone(),
+            min_lower: policy.min_lower,
+            min_upper: policy.min_upper,
+            min_digits: policy.min_digits,
+            min_symbols: policy.min_symbols,
+            min_custom: policy.min_custom,
+        };
+        Ok(cli.or(&fallback))
+    }
+}
+
+/// The document printed with --json; the password is left out if it is stored or copied
+#[derive(Serialize, Debug)]
+struct Generated<'a> {
+    password: Option<&'a str>,
+    key: Option<&'a str>,
+    entropy: f64,
+    score: u8,
+    strength: &'static str,
 }
 
-pub fn run(store: PathBuf, args: GenerateArgs) -> anyhow::Result<()> {
-    let pass = match &args.r#type {
-        Type::Phrase => generate_passphrase(args.length(), &args.separator),
-        Type::Word => generate_password(args.length()),
-    };
-    output(&store, args, pass)?;
+pub fn run(store: PathBuf, args: GenerateArgs, json: bool) -> anyhow::Result<()> {
+    let generator = args.profile(&store)?.generator(&store)?;
+    let mut pass = generator.generate()?;
+    if let Some(breach_db) = &args.breach_db {
+        let mut database = BreachDatabase::open(breach_db)?;
+        let mut attempts = 1;
+        while database.lookup(&pass)?.is_some() {
+            if attempts == MAX_BREACH_ATTEMPTS {
+                return Err(anyhow!(
+                    "All {attempts} generated passwords have been found in breaches"
+                ));
+            }
+            pass = generator.generate()?;
+            attempts += 1;
+        }
+    }
+    let strength = Strength::from_entropy(generator.entropy());
+    let printed = (!args.clipboard && args.store.is_none()).then(|| pass.clone());
+    let key = args.store.clone();
+    output(&store, args, pass, json)?;
+    if json {
+        let generated = Generated {
+            password: printed.as_deref(),
+            key: key.as_deref(),
+            entropy: strength.entropy,
+            score: strength.score,
+            strength: strength.label(),
+        };
+        serde_json::to_writer_pretty(io::stdout(), &generated)?;
+        println!();
+    } else {
+        print_strength(strength);
+    }
     Ok(())
 }
 
-fn output(store: &Path, args: GenerateArgs, pass: String) -> anyhow::Result<()> {
-    if !args.clipboard && args.store.is_none() {
+fn output(store: &Path, args: GenerateArgs, pass: String, json: bool) -> anyhow::Result<()> {
+    if !json && !args.clipboard && args.store.is_none() {
         if !args.no_anim && io::stdout().is_terminal() {
             animate(&pass);
         } else if io::stdout().is_terminal() {
@@ -1,8 +1,9 @@
 use crate::command::clip;
-use crate::key::Key;
+use crate::command::export::record;
+use crate::key::{Key, key_of, resolve_existing};
 use clap::Args;
 use color_print::cstr;
-use entrust_core::{Backend, resolve_existing_location};
+use entrust_core::Backend;
 use std::io;
 use std::io::IsTerminal;
 use std::path::PathBuf;
@@ -13,7 +14,9 @@ pub(super) const LONG_ABOUT: &str = cstr!("
 
   Decrypt a password
 
-  The age identity for age-encrypted files can be provided in <bold,#ffb86c>AGE_IDENTITY</> or piped into <bold,#ffb86c>stdin</>");
+  The age identity for age-encrypted files can be provided in <bold,#ffb86c>AGE_IDENTITY</> or piped into <bold,#ffb86c>stdin</>
+
+  With <bold,#ffb86c>--json</>, prints the entry as a record like <bold,#ffb86c>export --format json</>");
 
 #[derive(Args, Debug)]
 pub struct GetArgs {
@@ -28,16 +31,20 @@ pub struct GetArgs {
     pub(super) clear_clipboard_delay: u64,
 }
 
-pub fn run(store: PathBuf, args: GetArgs) -> anyhow::Result<()> {
+pub fn run(store: PathBuf, args: GetArgs, json: bool) -> anyhow::Result<()> {
     let location = &args
         .key
         .unwrap_or_select_existing(&store)
-        .and_then(|key| resolve_existing_location(&store, &key, false))?;
+        .and_then(|key| resolve_existing(&store, &key, false))?;
     let decrypted = Backend::decrypt(location)?;
 
     if args.clipboard && args.clear_clipboard_delay > 0 {
         clip::clear_in_new_process(decrypted.as_str(), args.clear_clipboard_delay)?;
         clip::copy(decrypted.into())?;
+    } else if json {
+        let record = record(&store, &key_of(&store, location)?, &decrypted)?;
+        serde_json::to_writer_pretty(io::stdout(), &record)?;
+        println!();
     } else {
         print!("{decrypted}");
         if io::stdout().is_terminal() {
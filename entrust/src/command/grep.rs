@@ -0,0 +1,122 @@
+use crate::bulk::decrypt_all;
+use crate::theme::{color, load_clap_theme};
+use clap::Args;
+use color_print::{cformat, cstr};
+use entrust_core::get_existing_keys;
+use regex::{Regex, RegexBuilder};
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+pub(super) const ABOUT: &str = "Search the contents of passwords";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Search the contents of passwords
+
+  Decrypts all entries below <bold,#ffb86c>DIR</> and prints the lines matching <bold,#ffb86c>PATTERN</>. \
+  The first line of an entry, which holds the password, is masked unless <bold,#ffb86c>--show-secrets</> is given"
+);
+
+const MASK: &str = "********";
+
+#[derive(Args, Debug)]
+pub struct GrepArgs {
+    /// The regular expression to search for
+    pattern: String,
+    /// The directory to search (default: the whole store)
+    dir: Option<String>,
+    /// Match case-insensitively
+    #[arg(short, long)]
+    ignore_case: bool,
+    /// Print matching password lines instead of masking them
+    #[arg(long)]
+    show_secrets: bool,
+}
+
+pub fn run(store: PathBuf, args: GrepArgs) -> anyhow::Result<()> {
+    let regex = RegexBuilder::new(&args.pattern)
+        .case_insensitive(args.ignore_case)
+        .build()?;
+    let keys = get_existing_keys(&store, args.dir.as_deref())?;
+    for (key, decrypted) in keys.iter().zip(decrypt_all(&store, &keys)) {
+        let content = match decrypted {
+            Ok(content) => content,
+            Err(err) => {
+                eprintln!("Could not decrypt {key}: {err}");
+                continue;
+            }
+        };
+        let lines = matching_lines(&content, &regex, args.show_secrets);
+        if lines.is_empty() {
+            continue;
+        }
+        print_key(key);
+        for (number, line) in lines {
+            if color() {
+                println!("{}{line}", cformat!("<bright-black>{number:>4}:</> "));
+            } else {
+                println!("{number:>4}: {line}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The matching lines with their 1-based line numbers; the first line is masked unless `show_secrets`
+fn matching_lines<'a>(
+    content: &'a str,
+    regex: &Regex,
+    show_secrets: bool,
+) -> Vec<(usize, Cow<'a, str>)> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| regex.is_match(line))
+        .map(|(index, line)| {
+            let line = if index == 0 && !show_secrets {
+                Cow::Borrowed(MASK)
+            } else if color() {
+                regex.replace_all(line, |caps: &regex::Captures| {
+                    cformat!("<bold,#ffb86c>{}</>", &caps[0])
+                })
+            } else {
+                Cow::Borrowed(line)
+            };
+            (index + 1, line)
+        })
+        .collect()
+}
+
+fn print_key(key: &str) {
+    if color() {
+        let theme = load_clap_theme();
+        println!(
+            "{}{key}{}",
+            theme.get_header().render(),
+            theme.get_header().render_reset()
+        );
+    } else {
+        println!("{key}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_lines() {
+        let content = "hunter2@example.com\nuser: me@example.com\nnote\nrecovery: you@example.com";
+        let regex = Regex::new(r"\w+@example\.com").unwrap();
+        let lines: Vec<_> = matching_lines(content, &regex, false)
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(vec![1, 2, 4], lines);
+        assert_eq!(MASK, matching_lines(content, &regex, false)[0].1);
+        let regex = Regex::new("^hunter").unwrap();
+        assert_eq!(1, matching_lines(content, &regex, true).len());
+        assert!(matching_lines(content, &Regex::new("nothing").unwrap(), true).is_empty());
+    }
+}
@@ -1,12 +1,218 @@
-use entrust_core::age;
-use std::io;
+use crate::bulk::{decrypt_all_or_abort, reencrypt_all, reencrypted_any};
+use crate::key::key_of;
+use anyhow::anyhow;
+use clap::{Args, Subcommand};
+use color_print::cstr;
+use entrust_core::age::identity::{
+    generate_identity, get_identity, recipients_of, refresh_identity,
+};
+use entrust_core::{Backend, edit_recipients, get_existing_keys, git, read_recipients};
 use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::{fs, io, slice};
 
-pub fn run() -> anyhow::Result<()> {
-    let age_identity = String::from_utf8(age::identity::get_identity()?.clone())?;
+pub(super) const ABOUT: &str = "Print or rotate the age identity";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Print or rotate the age identity
+
+  Without a subcommand, prints the current age identity.
+  <bold,#ffb86c>rotate</> generates a new identity, replaces the recipient of the current one \
+  in every <bold,#ffb86c>.age-id</> of the store and re-encrypts the affected entries"
+);
+
+#[derive(Args, Debug)]
+pub struct IdentityArgs {
+    #[command(subcommand)]
+    command: Option<IdentitySubcommand>,
+}
+
+#[derive(Subcommand, Debug)]
+enum IdentitySubcommand {
+    /// Replace the current age identity with a newly generated one
+    Rotate(RotateArgs),
+}
+
+#[derive(Args, Debug)]
+struct RotateArgs {
+    /// The file to write the new identity to
+    #[arg(short, long, value_name = "FILE")]
+    output: PathBuf,
+    /// Encrypt the new identity with a passphrase
+    #[arg(short, long)]
+    passphrase: bool,
+    /// Do not commit the change to the git repository if one exists
+    #[arg(long = "no-git")]
+    no_git: bool,
+}
+
+pub fn run(store: PathBuf, args: IdentityArgs) -> anyhow::Result<()> {
+    match args.command {
+        None => print_identity(),
+        Some(IdentitySubcommand::Rotate(args)) => rotate(&store, args),
+    }
+}
+
+fn print_identity() -> anyhow::Result<()> {
+    let age_identity = String::from_utf8(get_identity()?.to_vec())?;
     print!("{age_identity}");
     if io::stdout().is_terminal() {
         println!();
     }
     Ok(())
 }
+
+fn rotate(store: &Path, args: RotateArgs) -> anyhow::Result<()> {
+    if args.output.exists() {
+        return Err(anyhow!("{} already exists", args.output.display()));
+    }
+    let old_recipients = recipients_of(get_identity()?.as_slice())?;
+    let (recipient_files, keys) = affected(store, &old_recipients)?;
+    let contents = decrypt_all_or_abort(store, &keys)?;
+    let previous = recipient_files
+        .iter()
+        .map(fs::read_to_string)
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let (new_identity, new_recipient) = generate_identity()?;
+    write_identity(&args.output, &new_identity, args.passphrase)?;
+    eprintln!(
+        "Wrote the new identity for {new_recipient} to {}",
+        args.output.display()
+    );
+
+    let rotated = recipient_files
+        .iter()
+        .zip(&previous)
+        .try_for_each(|(file, content)| {
+            fs::write(
+                file,
+                edit_recipients(content, &old_recipients, slice::from_ref(&new_recipient)),
+            )
+        });
+    let reencrypted = rotated.map_err(anyhow::Error::from).and_then(|()| {
+        reencrypt_all(store, Backend::Age, &keys, contents).map_err(anyhow::Error::from)
+    });
+    if let Err(err) = reencrypted {
+        if reencrypted_any(&err) {
+            return Err(err.context(
+                "Kept the changed .age-id files; the entries re-encrypted so far require the new identity",
+            ));
+        }
+        for (file, content) in recipient_files.iter().zip(previous) {
+            fs::write(file, content)?;
+        }
+        return Err(err.context(format!(
+            "Restored the .age-id files; the new identity in {} is not used",
+            args.output.display()
+        )));
+    }
+    eprintln!("Re-encrypted {} entries", keys.len());
+    refresh_identity(new_identity.as_bytes())?;
+
+    if !args.no_git {
+        let mut files = keys;
+        for file in &recipient_files {
+            files.push(key_of(store, file)?);
+        }
+        git::update_recipients(store, &files, "rotate age identity")?;
+    }
+    eprintln!(
+        "Set AGE_IDENTITY to {} and delete the old identity",
+        args.output.display()
+    );
+    Ok(())
+}
+
+/// The recipient files containing one of the old recipients and the age entries they apply to
+fn affected(
+    store: &Path,
+    old_recipients: &[String],
+) -> anyhow::Result<(Vec<PathBuf>, Vec<String>)> {
+    let recipient_files: Vec<_> = Backend::Age
+        .recipient_files(store)?
+        .into_iter()
+        .filter(|file| {
+            read_recipients(file)
+                .is_ok_and(|recipients| recipients.iter().any(|r| old_recipients.contains(r)))
+        })
+        .collect();
+    if recipient_files.is_empty() {
+        return Err(anyhow!(
+            "No .age-id in the store contains the recipient of the current identity"
+        ));
+    }
+    let keys = get_existing_keys(store, None)?
+        .into_iter()
+        .filter(|key| {
+            let location = store.join(key);
+            matches!(Backend::detect(&location), Ok(Backend::Age))
+                && Backend::Age
+                    .recipient_file(store, &location)
+                    .is_some_and(|file| recipient_files.contains(&file))
+        })
+        .collect();
+    Ok((recipient_files, keys))
+}
+
+fn write_identity(output: &Path, identity: &str, passphrase: bool) -> anyhow::Result<()> {
+    if passphrase {
+        entrust_core::age::encrypt_with_passphrase(&mut identity.as_bytes(), output)
+    } else {
+        write_private(output, identity)
+    }
+}
+
+#[cfg(unix)]
+fn write_private(output: &Path, identity: &str) -> anyhow::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(output)?
+        .write_all(identity.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_private(output: &Path, identity: &str) -> anyhow::Result<()> {
+    fs::write(output, identity)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entrust_core::write_recipients;
+    use tempfile::TempDir;
+
+    const AGE_HEADER: &str = "age-encryption.org/v1\n";
+
+    fn setup() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path();
+        fs::create_dir_all(store.join("work")).unwrap();
+        fs::create_dir_all(store.join("shared")).unwrap();
+        write_recipients(&store.join(".age-id"), &["age1old".to_string()]).unwrap();
+        write_recipients(&store.join("shared/.age-id"), &["age1other".to_string()]).unwrap();
+        fs::write(store.join("mail"), AGE_HEADER).unwrap();
+        fs::write(store.join("work/github"), AGE_HEADER).unwrap();
+        fs::write(store.join("work/gpg"), "-----BEGIN PGP MESSAGE-----\n").unwrap();
+        fs::write(store.join("shared/wifi"), AGE_HEADER).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_affected() {
+        let dir = setup();
+        let (files, keys) = affected(dir.path(), &["age1old".to_string()]).unwrap();
+        assert_eq!(vec![dir.path().join(".age-id")], files);
+        assert_eq!(vec!["mail", "work/github"], keys);
+        let unknown = affected(dir.path(), &["age1unknown".to_string()]);
+        assert!(unknown.is_err_and(|e| e.to_string().contains("No .age-id")));
+    }
+}
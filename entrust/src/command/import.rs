@@ -0,0 +1,151 @@
+use crate::command::BackendValueEnum;
+use crate::command::export::Record;
+use anyhow::anyhow;
+use clap::{ArgGroup, Args, ValueEnum};
+use color_print::cstr;
+use entrust_core::archive::ArchiveEntry;
+use entrust_core::{Backend, git, is_valid_key, resolve_new_location};
+use itertools::Itertools;
+use std::fs::File;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+pub(super) const ABOUT: &str = "Import entries from an export";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Import entries from an export
+
+  The entries are re-encrypted with the chosen backend to the recipients of this store. \
+  The age identity for archives encrypted to a recipient can be provided in \
+  <bold,#ffb86c>AGE_IDENTITY</> or piped into <bold,#ffb86c>stdin</>.
+  With <bold,#ffb86c>--format</>, reads the records from the given file or \
+  <bold,#ffb86c>stdin</>; their backend is ignored"
+);
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("input").required(true)))]
+pub struct ImportArgs {
+    /// The file to read records from when using --format (default: stdin)
+    #[arg(requires = "format")]
+    file: Option<PathBuf>,
+    /// The encrypted archive to import
+    #[arg(short, long, value_name = "FILE", group = "input")]
+    archive: Option<PathBuf>,
+    /// The archive is encrypted with a passphrase
+    #[arg(short, long, requires = "archive")]
+    passphrase: bool,
+    /// Read plaintext records in the given format
+    #[arg(short, long, value_enum, group = "input")]
+    format: Option<Format>,
+    /// Choose gpg or age for re-encryption
+    #[arg(short, long, value_enum, default_value_t = BackendValueEnum::Age)]
+    pub backend: BackendValueEnum,
+    /// Do not add the imported files to git
+    #[arg(long = "no-git")]
+    no_git: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Json,
+}
+
+pub fn run(store: PathBuf, args: ImportArgs) -> anyhow::Result<()> {
+    let entries = match (&args.archive, &args.format) {
+        (Some(archive), _) => entrust_core::archive::read(archive, args.passphrase)?,
+        (None, Some(Format::Json)) => read_json(args.file.as_deref())?,
+        (None, None) => Vec::new(),
+    };
+    let existing = entries
+        .iter()
+        .filter(|e| resolve_new_location(&store, &e.key).is_err())
+        .map(|e| e.key.as_str())
+        .join(", ");
+    if !existing.is_empty() {
+        return Err(anyhow!("Keys already exist: {existing}"));
+    }
+    let duplicates = entries
+        .iter()
+        .map(|e| e.key.as_str())
+        .duplicates()
+        .join(", ");
+    if !duplicates.is_empty() {
+        return Err(anyhow!("Keys occur more than once: {duplicates}"));
+    }
+    let backend = Backend::from(args.backend);
+    let mut keys = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let location = resolve_new_location(&store, &entry.key)?;
+        if let Some(parent) = location.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        backend.encrypt(entry.content.as_bytes(), &store, &location)?;
+        if let Some(modified) = entry.modified {
+            File::options()
+                .write(true)
+                .open(&location)?
+                .set_modified(modified)?;
+        }
+        keys.push(entry.key);
+    }
+    if !args.no_git && !keys.is_empty() {
+        git::import(&store, &keys)?;
+    }
+    eprintln!("Imported {} entries", keys.len());
+    Ok(())
+}
+
+fn read_json(file: Option<&Path>) -> anyhow::Result<Vec<ArchiveEntry>> {
+    let records: Vec<Record> = match file {
+        Some(file) => serde_json::from_reader(File::open(file)?)?,
+        None if io::stdin().is_terminal() => return Err(anyhow!("No records given on stdin")),
+        None => serde_json::from_reader(io::stdin())?,
+    };
+    records
+        .into_iter()
+        .map(|record| {
+            if !is_valid_key(&record.key) {
+                return Err(anyhow!("Invalid key: {}", record.key));
+            }
+            let modified = record
+                .modified
+                .as_deref()
+                .map(humantime::parse_rfc3339_weak)
+                .transpose()?;
+            Ok(ArchiveEntry {
+                key: record.key.clone(),
+                content: record.into_entry().to_string(),
+                modified,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_keys() {
+        let store = tempfile::tempdir().unwrap();
+        let file = store.path().join(".records.json");
+        let record = r#"{"key": "mail", "password": "hunter2"}"#;
+        fs::write(&file, format!("[{record}, {record}]")).unwrap();
+        let result = run(
+            store.path().to_path_buf(),
+            ImportArgs {
+                file: Some(file),
+                archive: None,
+                passphrase: false,
+                format: Some(Format::Json),
+                backend: BackendValueEnum::Age,
+                no_git: true,
+            },
+        );
+        assert!(result.is_err_and(|e| e.to_string().contains("more than once: mail")));
+        assert!(!store.path().join("mail").exists());
+    }
+}
@@ -1,34 +1,329 @@
+use crate::dialog;
 use crate::key::Key;
 use crate::theme::chevron_prompt;
+use anyhow::anyhow;
 use clap::Args;
+use color_print::cstr;
 use entrust_core::git;
+use entrust_core::{get_existing_keys, is_valid_key};
 use entrust_core::{resolve_existing_location, resolve_new_location};
+use regex::Regex;
+use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub(super) const ABOUT: &str = "Move a password to another location in the store";
 
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Move a password to another location in the store
+
+  With <bold,#ffb86c>--regex</> or <bold,#ffb86c>--glob</>, every key matching <bold,#ffb86c>FROM</> is moved \
+  to <bold,#ffb86c>TO</>, in which <bold,#ffb86c>$1</>, <bold,#ffb86c>$2</>, ... refer to the groups of the \
+  regular expression or the wildcards of the glob, e.g. <bold,#ffb86c>ent mv --glob 'work/*' 'work/team/$1'</>.
+  All moves are checked for collisions and confirmed before the first one is made, \
+  unless <bold,#ffb86c>--force</> is given, and are committed together."
+);
+
 #[derive(Args, Debug)]
 pub struct MoveArgs {
-    #[arg()]
+    /// The key to move, or the pattern of the keys to move
+    #[arg(required_if_eq_any([("regex", "true"), ("glob", "true")]))]
     from: Option<String>,
-    #[arg()]
+    /// The new key, or the replacement for the matched keys
+    #[arg(required_if_eq_any([("regex", "true"), ("glob", "true")]))]
     to: Option<String>,
+    /// Move all keys matching the regular expression FROM
+    #[arg(long, conflicts_with = "glob")]
+    regex: bool,
+    /// Move all keys matching the glob FROM; * and ? do not match /, ** does
+    #[arg(long)]
+    glob: bool,
+    /// Only print the moves which would be made
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+    /// Overwrite an existing entry at the new key, or move the matching keys, without asking for
+    /// confirmation
+    #[arg(short, long)]
+    force: bool,
 }
 
 pub fn run(store: PathBuf, args: MoveArgs) -> anyhow::Result<()> {
+    if args.regex || args.glob {
+        return run_bulk(store, args);
+    }
     let from = &args.from.unwrap_or_select_existing(&store)?;
     let to = &args
         .to
         .unwrap_or_read_new(chevron_prompt!("New key"), &store)?;
     let from_location = resolve_existing_location(&store, from, true)?;
-    let to_location = resolve_new_location(&store, to)?;
+    let to_location = store.join(to);
+    let overwrite = dialog::trash_for_overwrite(&store, to, args.force)?;
+    if !overwrite {
+        resolve_new_location(&store, to)?;
+    }
     if let Some(dir) = to_location.parent() {
         fs::create_dir_all(dir)?;
     }
-    let git_moved = git::r#move(&store, from, to)?;
+    let git_moved = git::r#move(&store, from, to, overwrite)?;
     if !git_moved {
         fs::rename(from_location, to_location)?;
     }
     Ok(())
 }
+
+fn run_bulk(store: PathBuf, args: MoveArgs) -> anyhow::Result<()> {
+    let (Some(pattern), Some(replacement)) = (args.from, args.to) else {
+        return Err(anyhow!("A pattern and a replacement are required"));
+    };
+    let regex = if args.glob {
+        Regex::new(&glob_to_regex(&pattern))?
+    } else {
+        Regex::new(&pattern)?
+    };
+    let keys = get_existing_keys(&store, None)?;
+    let moves = plan(&store, &keys, &regex, &replacement)?;
+    if moves.is_empty() {
+        return Err(anyhow!("No key matches {pattern}"));
+    }
+    print_moves(&moves);
+    if args.dry_run {
+        return Ok(());
+    }
+    if !args.force {
+        dialog::require_terminal(&format!("move {} entries", moves.len()))?;
+        dialog::confirm_or_cancel(format!("Move {} entries?", moves.len()))?;
+    }
+
+    rename_all(&store, &moves)?;
+    for (from, _) in &moves {
+        remove_empty_parents(&store, &store.join(from))?;
+    }
+    git::move_all(&store, &moves)?;
+    eprintln!("Moved {} entries", moves.len());
+    Ok(())
+}
+
+/// Renames the entries, moving those renamed so far back if one of them cannot be renamed
+fn rename_all(store: &Path, moves: &[(String, String)]) -> anyhow::Result<()> {
+    for (i, (from, to)) in moves.iter().enumerate() {
+        let renamed = store
+            .join(to)
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::rename(store.join(from), store.join(to)));
+        if let Err(err) = renamed {
+            for (from, to) in moves[..=i].iter().rev() {
+                if store.join(to).is_file() {
+                    fs::rename(store.join(to), store.join(from))?;
+                }
+                remove_empty_parents(store, &store.join(to))?;
+            }
+            return Err(anyhow::Error::from(err)
+                .context(format!("Could not move {from} to {to}; nothing was moved")));
+        }
+    }
+    Ok(())
+}
+
+/// The moves of all keys matching the regex to their replacement,
+/// failing if a new key is invalid, already exists, is the target of another move
+/// or would be above or below the target of another move
+fn plan(
+    store: &Path,
+    keys: &[String],
+    regex: &Regex,
+    replacement: &str,
+) -> anyhow::Result<Vec<(String, String)>> {
+    let mut moves = Vec::new();
+    let mut targets = HashSet::new();
+    for key in keys {
+        if !regex.is_match(key) {
+            continue;
+        }
+        let new_key = regex.replace(key, replacement).into_owned();
+        if new_key == *key {
+            continue;
+        }
+        if new_key.ends_with('/') || !is_valid_key(&new_key) {
+            return Err(anyhow!("Cannot move {key} to invalid key {new_key}"));
+        }
+        if !targets.insert(new_key.clone()) {
+            return Err(anyhow!("Multiple keys would be moved to {new_key}"));
+        }
+        for target in &targets {
+            if target.starts_with(&format!("{new_key}/")) {
+                return Err(anyhow!(
+                    "Cannot move {key} to {new_key}; another key would be moved to {target} below it"
+                ));
+            }
+            if new_key.starts_with(&format!("{target}/")) {
+                return Err(anyhow!(
+                    "Cannot move {key} to {new_key}; another key would be moved to {target} above it"
+                ));
+            }
+        }
+        resolve_new_location(store, &new_key)
+            .map_err(|e| e.context(format!("Cannot move {key}")))?;
+        if let Some(file) = Path::new(&new_key)
+            .ancestors()
+            .skip(1)
+            .find(|a| store.join(a).is_file())
+        {
+            return Err(anyhow!(
+                "Cannot move {key} to {new_key}; {} is an entry",
+                file.display()
+            ));
+        }
+        moves.push((key.clone(), new_key));
+    }
+    Ok(moves)
+}
+
+/// Translates a glob to an anchored regex with a group for every wildcard
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str("(.*)");
+            }
+            '*' => regex.push_str("([^/]*)"),
+            '?' => regex.push_str("([^/])"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+fn print_moves(moves: &[(String, String)]) {
+    let width = moves.iter().map(|(from, _)| from.len()).max().unwrap_or(0);
+    for (from, to) in moves {
+        eprintln!("  {from:width$}  ->  {to}");
+    }
+}
+
+fn remove_empty_parents(store: &Path, location: &Path) -> anyhow::Result<()> {
+    for dir in location.ancestors().skip(1) {
+        if dir == store || !dir.is_dir() || dir.read_dir()?.next().is_some() {
+            break;
+        }
+        fs::remove_dir(dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entrust_core::trash;
+    use std::fs::File;
+    use std::io;
+    use tempfile::TempDir;
+
+    fn setup() -> io::Result<TempDir> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir_all(dir.path().join("work/a"))?;
+        File::create(dir.path().join("work/github"))?;
+        File::create(dir.path().join("work/a/jira"))?;
+        File::create(dir.path().join("mail"))?;
+        Ok(dir)
+    }
+
+    fn keys(dir: &TempDir) -> Vec<String> {
+        get_existing_keys(dir.path(), None).unwrap()
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        let regex = Regex::new(&glob_to_regex("work/*")).unwrap();
+        assert!(regex.is_match("work/github"));
+        assert!(!regex.is_match("work/a/jira"));
+        let regex = Regex::new(&glob_to_regex("work/**")).unwrap();
+        assert!(regex.is_match("work/a/jira"));
+        let regex = Regex::new(&glob_to_regex("m?il.x")).unwrap();
+        assert!(regex.is_match("mail.x"));
+        assert!(!regex.is_match("mailxx"));
+    }
+
+    #[test]
+    fn test_plan() {
+        let dir = setup().unwrap();
+        let regex = Regex::new("^work/(.*)$").unwrap();
+        let moves = plan(dir.path(), &keys(&dir), &regex, "old/$1").unwrap();
+        assert_eq!(
+            vec![
+                ("work/a/jira".to_string(), "old/a/jira".to_string()),
+                ("work/github".to_string(), "old/github".to_string()),
+            ],
+            moves
+        );
+    }
+
+    #[test]
+    fn test_plan_collisions() {
+        let dir = setup().unwrap();
+        let regex = Regex::new("^work/(.*)$").unwrap();
+        let same_target = plan(dir.path(), &keys(&dir), &regex, "other");
+        assert!(same_target.is_err_and(|e| e.to_string().contains("Multiple keys")));
+        let regex = Regex::new("^work/github$").unwrap();
+        let existing = plan(dir.path(), &keys(&dir), &regex, "mail");
+        assert!(existing.is_err());
+        let below_entry = plan(dir.path(), &keys(&dir), &regex, "mail/github");
+        assert!(below_entry.is_err_and(|e| e.to_string().contains("is an entry")));
+        let trailing_slash = plan(dir.path(), &keys(&dir), &regex, "old/");
+        assert!(trailing_slash.is_err_and(|e| e.to_string().contains("invalid key")));
+        let regex = Regex::new("^mail$|^work/a(/jira)$").unwrap();
+        let nested = plan(dir.path(), &keys(&dir), &regex, "t$1");
+        assert!(nested.is_err_and(|e| e.to_string().contains("above it")));
+        let regex = Regex::new("^work/a(/jira)$|^work/github$").unwrap();
+        let nested = plan(dir.path(), &keys(&dir), &regex, "t$1");
+        assert!(nested.is_err_and(|e| e.to_string().contains("below it")));
+    }
+
+    #[test]
+    fn test_bulk_move() {
+        let dir = setup().unwrap();
+        let result = run(
+            dir.path().to_path_buf(),
+            MoveArgs {
+                from: Some("work/**".to_string()),
+                to: Some("work/team/$1".to_string()),
+                regex: false,
+                glob: true,
+                dry_run: false,
+                force: true,
+            },
+        );
+        assert!(result.is_ok());
+        assert_eq!(
+            vec!["mail", "work/team/a/jira", "work/team/github"],
+            keys(&dir)
+        );
+        assert!(!dir.path().join("work/a").exists());
+    }
+
+    #[test]
+    fn test_overwrite() {
+        let dir = setup().unwrap();
+        let result = run(
+            dir.path().to_path_buf(),
+            MoveArgs {
+                from: Some("work/github".to_string()),
+                to: Some("mail".to_string()),
+                regex: false,
+                glob: false,
+                dry_run: false,
+                force: true,
+            },
+        );
+        assert!(result.is_ok());
+        assert_eq!(vec!["mail", "work/a/jira"], keys(&dir));
+        let trashed: Vec<_> = trash::list(dir.path()).unwrap();
+        assert_eq!("mail", trashed[0].key);
+    }
+}
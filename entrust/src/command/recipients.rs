@@ -0,0 +1,313 @@
+use crate::bulk::{decrypt_all_or_abort, reencrypt_all, reencrypted_any};
+use crate::key::key_of;
+use anyhow::anyhow;
+use clap::{Args, Subcommand};
+use color_print::cstr;
+use entrust_core::{
+    Backend, edit_recipients, get_existing_keys, git, read_recipients, resolve_existing_location,
+};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(super) const ABOUT: &str = "List, add or remove the recipients entries are encrypted to";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  List, add or remove the recipients entries are encrypted to
+
+  Entries are encrypted to the recipients in the nearest <bold,#ffb86c>.age-id</> or \
+  <bold,#ffb86c>.gpg-id</> of their directory or a parent directory.
+  Adding or removing a recipient updates the recipient file of <bold,#ffb86c>--dir</> \
+  (creating it from the inherited recipients if needed) and re-encrypts the entries it applies to, \
+  so removed recipients cannot read them anymore. Their previous versions remain in the git history."
+);
+
+#[derive(Args, Debug)]
+pub struct RecipientsArgs {
+    #[command(subcommand)]
+    command: RecipientsSubcommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum RecipientsSubcommand {
+    /// List the recipients which apply to a directory
+    List {
+        /// The directory whose recipients to list (default: the root of the store)
+        #[arg(long)]
+        dir: Option<String>,
+    },
+    /// Add a recipient and re-encrypt the affected entries
+    Add(ChangeArgs),
+    /// Remove a recipient and re-encrypt the affected entries
+    Remove(ChangeArgs),
+}
+
+#[derive(Args, Debug)]
+struct ChangeArgs {
+    /// An age or ssh recipient, or the key ID, fingerprint or email address of a gpg key
+    recipient: String,
+    /// The directory whose recipient file to change (default: the root of the store)
+    #[arg(long)]
+    dir: Option<String>,
+    /// Do not commit the change to the git repository if one exists
+    #[arg(long = "no-git")]
+    no_git: bool,
+}
+
+#[derive(Serialize, Debug)]
+struct Listed {
+    backend: &'static str,
+    file: String,
+    recipients: Vec<String>,
+}
+
+pub fn run(store: PathBuf, args: RecipientsArgs, json: bool) -> anyhow::Result<()> {
+    match args.command {
+        RecipientsSubcommand::List { dir } => list(&store, dir.as_deref(), json),
+        RecipientsSubcommand::Add(args) => change(&store, args, Change::Add),
+        RecipientsSubcommand::Remove(args) => change(&store, args, Change::Remove),
+    }
+}
+
+fn list(store: &Path, dir: Option<&str>, json: bool) -> anyhow::Result<()> {
+    let dir = directory(store, dir)?;
+    let mut listed = Vec::new();
+    for backend in [Backend::Age, Backend::Gpg] {
+        let own_file = dir.join(backend.recipient_file_name());
+        if let Some(file) = backend.recipient_file(store, &own_file) {
+            listed.push(Listed {
+                backend: backend.display_name(),
+                file: key_of(store, &file)?,
+                recipients: read_recipients(&file)?,
+            });
+        }
+    }
+    if json {
+        serde_json::to_writer_pretty(std::io::stdout(), &listed)?;
+        println!();
+        return Ok(());
+    }
+    if listed.is_empty() {
+        return Err(anyhow!("No recipient file applies to {}", dir.display()));
+    }
+    for listed in listed {
+        println!("{} ({}):", listed.backend, listed.file);
+        for recipient in listed.recipients {
+            println!("  {recipient}");
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Change {
+    Add,
+    Remove,
+}
+
+fn change(store: &Path, args: ChangeArgs, change: Change) -> anyhow::Result<()> {
+    let dir = directory(store, args.dir.as_deref())?;
+    let recipient = args.recipient.trim().to_string();
+    let backend = if recipient.starts_with("age1") || recipient.starts_with("ssh-") {
+        Backend::Age
+    } else {
+        Backend::Gpg
+    };
+    if change == Change::Add {
+        backend.check_recipient(&recipient)?;
+    }
+    let file = dir.join(backend.recipient_file_name());
+    let previous = fs::read(&file).ok();
+    let inherited = backend.recipient_file(store, &file);
+    let content = changed_content(inherited.as_deref(), backend, &dir, &recipient, change)?;
+    fs::write(&file, content)?;
+    if let Some(inherited) = inherited.filter(|inherited| inherited != &file) {
+        eprintln!(
+            "Created {} from the recipients of {}",
+            key_of(store, &file)?,
+            key_of(store, &inherited)?
+        );
+    }
+
+    let reencrypted = match reencrypt(store, backend, &file) {
+        Ok(reencrypted) => reencrypted,
+        Err(err) => {
+            if reencrypted_any(&err) {
+                return Err(err.context(format!(
+                    "Kept the changed {}; the re-encrypted entries require it",
+                    key_of(store, &file)?
+                )));
+            }
+            match previous {
+                Some(previous) => fs::write(&file, previous)?,
+                None => fs::remove_file(&file)?,
+            }
+            return Err(err);
+        }
+    };
+    eprintln!("Re-encrypted {} entries", reencrypted.len());
+
+    if !args.no_git {
+        let mut files = reencrypted;
+        files.push(key_of(store, &file)?);
+        let message = match change {
+            Change::Add => format!("add recipient {recipient}"),
+            Change::Remove => format!("remove recipient {recipient}"),
+        };
+        let message = match &args.dir {
+            Some(dir) => format!("{message} for {dir}"),
+            None => message,
+        };
+        git::update_recipients(store, &files, &message)?;
+    }
+    Ok(())
+}
+
+/// The content of the inherited recipient file after the change, which keeps its comments;
+/// fails if the change is not possible
+fn changed_content(
+    inherited: Option<&Path>,
+    backend: Backend,
+    dir: &Path,
+    recipient: &str,
+    change: Change,
+) -> anyhow::Result<String> {
+    let (content, recipients) = match inherited {
+        Some(inherited) => (fs::read_to_string(inherited)?, read_recipients(inherited)?),
+        None if change == Change::Add => (String::new(), Vec::new()),
+        None => {
+            return Err(anyhow!(
+                "No {} applies to {}",
+                backend.recipient_file_name(),
+                dir.display()
+            ));
+        }
+    };
+    if recipients.iter().any(|r| r == recipient) == (change == Change::Add) {
+        return Err(match change {
+            Change::Add => anyhow!("{recipient} is already a recipient"),
+            Change::Remove => anyhow!("{recipient} is not a recipient"),
+        });
+    }
+    let recipient = [recipient.to_string()];
+    match change {
+        Change::Add => Ok(edit_recipients(&content, &[], &recipient)),
+        Change::Remove if recipients.len() == 1 => Err(anyhow!("Cannot remove the last recipient")),
+        Change::Remove => Ok(edit_recipients(&content, &recipient, &[])),
+    }
+}
+
+/// Re-encrypts the entries which the recipient file applies to.
+/// Nothing is changed if any of them cannot be decrypted.
+fn reencrypt(store: &Path, backend: Backend, file: &Path) -> anyhow::Result<Vec<String>> {
+    let dir = file.parent().unwrap_or(store);
+    let dir_key = key_of(store, dir)?;
+    let keys: Vec<_> = get_existing_keys(store, Some(&dir_key))?
+        .into_iter()
+        .filter(|key| {
+            let location = store.join(key);
+            Backend::detect(&location).is_ok_and(|b| b == backend)
+                && backend.recipient_file(store, &location).as_deref() == Some(file)
+        })
+        .collect();
+    let contents = decrypt_all_or_abort(store, &keys)?;
+    reencrypt_all(store, backend, &keys, contents)?;
+    Ok(keys)
+}
+
+/// The directory in the store, which must exist
+fn directory(store: &Path, dir: Option<&str>) -> anyhow::Result<PathBuf> {
+    let Some(dir) = dir.filter(|d| !d.trim_end_matches('/').is_empty()) else {
+        return Ok(store.to_path_buf());
+    };
+    let location = resolve_existing_location(store, dir, true)?;
+    if location.is_dir() {
+        Ok(location)
+    } else {
+        Err(anyhow!("{dir} is not a directory"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("work")).unwrap();
+        fs::write(
+            dir.path().join(".age-id"),
+            "# the team\nage1first\n\n# backup\nage1second\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_inherit_parent_file() {
+        let dir = setup();
+        let work = dir.path().join("work");
+        let inherited = Backend::Age.recipient_file(dir.path(), &work.join(".age-id"));
+        assert_eq!(Some(dir.path().join(".age-id")), inherited);
+        let content = changed_content(
+            inherited.as_deref(),
+            Backend::Age,
+            &work,
+            "age1third",
+            Change::Add,
+        )
+        .unwrap();
+        assert_eq!(
+            "# the team\nage1first\n\n# backup\nage1second\nage1third\n",
+            content
+        );
+    }
+
+    #[test]
+    fn test_create_new_file() {
+        let dir = setup();
+        let work = dir.path().join("work");
+        let inherited = Backend::Gpg.recipient_file(dir.path(), &work.join(".gpg-id"));
+        assert_eq!(None, inherited);
+        let content =
+            changed_content(None, Backend::Gpg, &work, "me@example.com", Change::Add).unwrap();
+        assert_eq!("me@example.com\n", content);
+        let removed = changed_content(None, Backend::Gpg, &work, "me@example.com", Change::Remove);
+        assert!(removed.is_err_and(|e| e.to_string().contains("No .gpg-id applies")));
+    }
+
+    #[test]
+    fn test_remove() {
+        let dir = setup();
+        let file = dir.path().join(".age-id");
+        let content = changed_content(
+            Some(&file),
+            Backend::Age,
+            dir.path(),
+            "age1first",
+            Change::Remove,
+        )
+        .unwrap();
+        assert_eq!("# the team\n\n# backup\nage1second\n", content);
+        fs::write(&file, content).unwrap();
+        let last = changed_content(
+            Some(&file),
+            Backend::Age,
+            dir.path(),
+            "age1second",
+            Change::Remove,
+        );
+        assert!(last.is_err_and(|e| e.to_string().contains("last recipient")));
+        let unknown = changed_content(
+            Some(&file),
+            Backend::Age,
+            dir.path(),
+            "age1other",
+            Change::Remove,
+        );
+        assert!(unknown.is_err_and(|e| e.to_string().contains("not a recipient")));
+    }
+}
@@ -1,13 +1,24 @@
+use crate::dialog;
 use crate::key::Key;
 use anyhow::anyhow;
 use clap::Args;
-use entrust_core::git;
+use color_print::cstr;
 use entrust_core::resolve_existing_location;
+use entrust_core::{git, trash};
 use std::fs;
 use std::path::PathBuf;
 
 pub(super) const ABOUT: &str = "Delete a password";
 
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Delete a password
+
+  Deleted entries are moved to the <bold,#ffb86c>.trash</> directory of the store, \
+  from which they can be brought back with <bold,#ffb86c>restore</> until the trash is emptied"
+);
+
 #[derive(Args, Debug)]
 pub struct RemoveArgs {
     /// The key to delete
@@ -16,23 +27,24 @@ pub struct RemoveArgs {
     /// Enable deleting directories
     #[arg(short, long)]
     recurse: bool,
+    /// Delete without asking for confirmation
+    #[arg(short, long)]
+    force: bool,
 }
 
 pub fn run(store: PathBuf, args: RemoveArgs) -> anyhow::Result<()> {
     let key = &args.key.unwrap_or_select_existing(&store)?;
     let location = resolve_existing_location(&store, key, true)?;
-    let is_dir = location.is_dir();
-    if is_dir && !args.recurse {
+    if location.is_dir() && !args.recurse {
         return Err(anyhow!(
             "{} is a directory; specify --recurse to delete",
             key
         ));
     }
-    if is_dir {
-        fs::remove_dir_all(&location)?;
-    } else {
-        fs::remove_file(&location)?;
-    };
+    if !args.force {
+        dialog::confirm_destructive(&store, "Delete", &[key])?;
+    }
+    trash::trash(&store, key)?;
     git::remove(&store, key)?;
     if let Some(parent) = location.parent()
         && parent.exists()
@@ -67,6 +79,7 @@ mod tests {
             RemoveArgs {
                 key: Some("subdir/file1".to_string()),
                 recurse: false,
+                force: true,
             },
         );
         assert!(result_file.is_ok());
@@ -76,6 +89,7 @@ mod tests {
             RemoveArgs {
                 key: Some("subdir".to_string()),
                 recurse: false,
+                force: true,
             },
         );
         assert!(result_dir.is_err_and(|e| e.to_string().contains("is a directory")));
@@ -90,6 +104,7 @@ mod tests {
             RemoveArgs {
                 key: Some("subdir/file1".to_string()),
                 recurse: true,
+                force: true,
             },
         );
         assert!(result_file.is_ok());
@@ -99,6 +114,7 @@ mod tests {
             RemoveArgs {
                 key: Some("subdir".to_string()),
                 recurse: true,
+                force: true,
             },
         );
         assert!(result_dir.is_ok());
@@ -113,6 +129,7 @@ mod tests {
             RemoveArgs {
                 key: Some("subdir/file1".to_string()),
                 recurse: false,
+                force: true,
             },
         );
         assert!(result_file.is_ok());
@@ -121,10 +138,12 @@ mod tests {
             RemoveArgs {
                 key: Some("subdir/file2".to_string()),
                 recurse: false,
+                force: true,
             },
         );
         assert!(result_file.is_ok());
 
         assert!(!dir.path().join("subdir").exists());
+        assert_eq!(2, trash::list(dir.path()).unwrap().len());
     }
 }
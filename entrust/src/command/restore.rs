@@ -0,0 +1,23 @@
+use clap::Args;
+use entrust_core::{git, trash};
+use std::path::PathBuf;
+
+pub(super) const ABOUT: &str = "Restore a deleted password from the trash";
+
+#[derive(Args, Debug)]
+pub struct RestoreArgs {
+    /// The key of the entry or directory to restore; its most recently deleted version is restored
+    key: String,
+    /// Do not commit the restored entry to the git repository if one exists
+    #[arg(long = "no-git")]
+    no_git: bool,
+}
+
+pub fn run(store: PathBuf, args: RestoreArgs) -> anyhow::Result<()> {
+    let key = args.key.trim_end_matches('/');
+    trash::restore(&store, key)?;
+    if !args.no_git {
+        git::restore(&store, key)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use crate::command::clip;
+use crate::key::{Key, key_of, resolve_existing};
+use crate::strength::print_strength;
+use clap::Args;
+use color_print::cstr;
+use entrust_core::{Backend, StoreConfig, Strength, git, replace_password};
+use std::path::PathBuf;
+
+pub(super) const ABOUT: &str = "Replace a password with a newly generated one";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Replace a password with a newly generated one
+
+  Generates a password with the generator profile mapped to the key in <bold,#ffb86c>.ent.toml</> \
+  and replaces the first line of the entry with it. \
+  All other lines and the encryption backend are kept."
+);
+
+#[derive(Args, Debug)]
+pub struct RotateArgs {
+    /// The key of the password to rotate
+    key: Option<String>,
+    /// Use the given generator profile of the store instead of the one mapped to the key
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+    /// Copy the new password to the clipboard
+    #[arg(short, long)]
+    clipboard: bool,
+    /// Clear the clipboard after the given number of seconds.
+    /// Pass 0 to disable clearing
+    #[arg(short = 'd', long, default_value = "10")]
+    clear_clipboard_delay: u64,
+    /// Do not commit the change to the git repository if one exists
+    #[arg(long)]
+    no_git: bool,
+}
+
+pub fn run(store: PathBuf, args: RotateArgs) -> anyhow::Result<()> {
+    let key = args.key.unwrap_or_select_existing(&store)?;
+    let location = resolve_existing(&store, &key, false)?;
+    let key = key_of(&store, &location)?;
+
+    let config = StoreConfig::load(&store)?;
+    let profile = match &args.profile {
+        Some(profile) => config.profile(profile)?,
+        None => config.profile_for_key(&key)?,
+    };
+    let generator = profile.generator(&store)?;
+    let pass = generator.generate()?;
+
+    let backend = Backend::detect(&location)?;
+    let rotated = replace_password(&Backend::decrypt(&location)?, &pass);
+
+    backend.encrypt_replacing(rotated.as_bytes(), &store, &location)?;
+    if !args.no_git {
+        git::rotate(&store, &key)?;
+    }
+
+    print_strength(Strength::from_entropy(generator.entropy()));
+    if args.clipboard && args.clear_clipboard_delay > 0 {
+        clip::clear_in_new_process(pass.as_str(), args.clear_clipboard_delay)?;
+        clip::copy(pass.into())?;
+    }
+    Ok(())
+}
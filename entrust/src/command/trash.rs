@@ -0,0 +1,92 @@
+use crate::dialog;
+use clap::{Args, Subcommand};
+use color_print::cstr;
+use entrust_core::trash;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub(super) const ABOUT: &str = "List or empty the trash of deleted passwords";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  List or empty the trash of deleted passwords
+
+  Deleted entries are kept in the <bold,#ffb86c>.trash</> directory of the store, which is never \
+  committed to git. Use <bold,#ffb86c>restore</> to bring an entry back."
+);
+
+#[derive(Args, Debug)]
+pub struct TrashArgs {
+    #[command(subcommand)]
+    command: TrashSubcommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum TrashSubcommand {
+    /// List the deleted entries, most recently deleted first
+    List,
+    /// Permanently delete the entries in the trash
+    Empty {
+        /// Only delete entries which were deleted longer ago than this, like 30d or 12h
+        #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+        older_than: Option<Duration>,
+        /// Delete the entries without asking for confirmation
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+#[derive(Serialize, Debug)]
+struct Listed {
+    key: String,
+    removed: String,
+}
+
+pub fn run(store: PathBuf, args: TrashArgs, json: bool) -> anyhow::Result<()> {
+    match args.command {
+        TrashSubcommand::List => list(&store, json),
+        TrashSubcommand::Empty { older_than, force } => empty(&store, older_than, force),
+    }
+}
+
+fn empty(store: &Path, older_than: Option<Duration>, force: bool) -> anyhow::Result<()> {
+    let expired = trash::list(store)?
+        .into_iter()
+        .filter(|entry| entry.is_expired(older_than))
+        .count();
+    if expired == 0 {
+        eprintln!("There are no entries to delete");
+        return Ok(());
+    }
+    if !force {
+        dialog::require_terminal(&format!("permanently delete {expired} entries"))?;
+        dialog::confirm_or_cancel(format!("Permanently delete {expired} entries?"))?;
+    }
+    let deleted = trash::empty(store, older_than)?;
+    eprintln!("Permanently deleted {deleted} entries");
+    Ok(())
+}
+
+fn list(store: &Path, json: bool) -> anyhow::Result<()> {
+    let listed: Vec<_> = trash::list(store)?
+        .into_iter()
+        .map(|entry| Listed {
+            key: entry.key,
+            removed: humantime::format_rfc3339_seconds(entry.removed).to_string(),
+        })
+        .collect();
+    if json {
+        serde_json::to_writer_pretty(std::io::stdout(), &listed)?;
+        println!();
+        return Ok(());
+    }
+    if listed.is_empty() {
+        eprintln!("The trash is empty");
+    }
+    for listed in listed {
+        println!("{}  {}", listed.removed, listed.key);
+    }
+    Ok(())
+}
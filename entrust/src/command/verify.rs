@@ -0,0 +1,112 @@
+use crate::bulk::decrypt_all;
+use crate::theme::print_heading;
+use anyhow::anyhow;
+use clap::Args;
+use color_print::cstr;
+use entrust_core::{Backend, get_existing_keys};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub(super) const ABOUT: &str = "Verify that all entries are readable and correctly addressed";
+
+pub(super) const LONG_ABOUT: &str = cstr!(
+    "
+
+  Verify that all entries are readable and correctly addressed
+
+  Decrypts all entries below <bold,#ffb86c>DIR</> with the current identity and checks that \
+  each one is encrypted to the recipients in the nearest <bold,#ffb86c>.age-id</> or \
+  <bold,#ffb86c>.gpg-id</> of its directory or a parent directory.
+  gpg files name the keys they are encrypted to; age files only reveal the number and type \
+  of their recipients, so an age entry addressed to a stale recipient shows up as unreadable.
+  Fails if any entry is unreadable or mis-addressed."
+);
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// The directory to verify (default: the whole store)
+    dir: Option<String>,
+}
+
+#[derive(Serialize, Debug, Default)]
+struct Report {
+    checked: usize,
+    unreadable: Vec<Failure>,
+    misaddressed: Vec<Failure>,
+}
+
+#[derive(Serialize, Debug)]
+struct Failure {
+    key: String,
+    reason: String,
+}
+
+pub fn run(store: PathBuf, args: VerifyArgs, json: bool) -> anyhow::Result<()> {
+    let keys = get_existing_keys(&store, args.dir.as_deref())?;
+    let mut report = Report {
+        checked: keys.len(),
+        ..Report::default()
+    };
+    for (key, decrypted) in keys.iter().zip(decrypt_all(&store, &keys)) {
+        if let Err(err) = decrypted {
+            report.unreadable.push(Failure {
+                key: key.clone(),
+                reason: err.to_string().trim().to_string(),
+            });
+        } else if let Err(reason) = check_addressed(&store, key) {
+            report.misaddressed.push(Failure {
+                key: key.clone(),
+                reason,
+            });
+        }
+    }
+
+    if json {
+        serde_json::to_writer_pretty(std::io::stdout(), &report)?;
+        println!();
+    } else {
+        print_report(&report);
+    }
+    let failed = report.unreadable.len() + report.misaddressed.len();
+    match failed {
+        0 => Ok(()),
+        _ => Err(anyhow!(
+            "{failed} of {} entries failed verification",
+            report.checked
+        )),
+    }
+}
+
+fn check_addressed(store: &Path, key: &str) -> Result<(), String> {
+    let location = store.join(key);
+    let backend = Backend::detect(&location).map_err(|e| e.to_string())?;
+    let recipients = backend
+        .applicable_recipients(store, &location)
+        .map_err(|e| e.to_string())?;
+    match backend.check_addressed(&location, &recipients) {
+        Ok(None) => Ok(()),
+        Ok(Some(misaddressed)) => Err(misaddressed.to_string()),
+        Err(err) => Err(format!("could not be checked: {err}")),
+    }
+}
+
+fn print_report(report: &Report) {
+    let width = report
+        .unreadable
+        .iter()
+        .chain(&report.misaddressed)
+        .map(|f| f.key.len())
+        .max()
+        .unwrap_or_default();
+    for (title, failures) in [
+        ("Unreadable", &report.unreadable),
+        ("Mis-addressed", &report.misaddressed),
+    ] {
+        print_heading(&format!("{title} ({})", failures.len()));
+        for failure in failures {
+            let reason = failure.reason.lines().next().unwrap_or_default();
+            println!("  {:width$}  {reason}", failure.key);
+        }
+    }
+    println!("\nChecked {} entries", report.checked);
+}
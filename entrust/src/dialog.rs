@@ -1,13 +1,18 @@
-use crate::theme::{DIALOG_THEME, chevron_prompt};
-use entrust_core::get_existing_locations;
-use entrust_dialog::dialog::Dialog;
+use crate::strength::describe;
+use crate::theme::{CHEVRON, DIALOG_THEME, chevron_prompt};
+use anyhow::anyhow;
+use entrust_core::{estimate_strength, get_existing_keys, get_existing_locations, trash};
+use entrust_dialog::dialog::{Cancelled, Dialog};
 use entrust_dialog::input::InputDialog;
 use entrust_dialog::input::confirmation::Confirmation;
 use entrust_dialog::input::mask::InputMask;
 use entrust_dialog::input::prompt::Prompt;
 use entrust_dialog::input::validator::{Validator, ValidatorFn, combine, validate_filename};
 use entrust_dialog::select::SelectDialog;
+use entrust_dialog::yes_no::YesNoDialog;
 use std::borrow::Cow;
+use std::io;
+use std::io::IsTerminal;
 use std::ops::Deref;
 use std::path::Path;
 
@@ -24,16 +29,20 @@ pub fn select_existing_key(store: &Path) -> anyhow::Result<String> {
     }
 }
 
-pub fn read_password_interactive(initial: &str) -> anyhow::Result<String> {
+pub fn read_password_interactive(initial: &str, min_score: Option<u8>) -> anyhow::Result<String> {
     let mask = if initial.is_empty() {
         InputMask::dots()
     } else {
         InputMask::none()
     };
+    let mut validator = Validator::not_empty("The password must not be empty.");
+    if let Some(min_score) = min_score {
+        validator = validator + Validator::new(validate_strength(min_score));
+    }
     let mut dialog = InputDialog::default()
         .with_content(initial)
         .with_prompt(Prompt::inline(chevron_prompt!("Enter new password")))
-        .with_validator(Validator::not_empty("The password must not be empty."))
+        .with_validator(validator)
         .with_mask(mask)
         .with_theme(DIALOG_THEME.deref());
     if initial.is_empty() {
@@ -43,6 +52,101 @@ pub fn read_password_interactive(initial: &str) -> anyhow::Result<String> {
     Ok(pass)
 }
 
+pub fn read_field_interactive(name: &str) -> anyhow::Result<String> {
+    let value = InputDialog::default()
+        .with_prompt(Prompt::inline(format!("{name} {CHEVRON} ")))
+        .with_placeholder("optional")
+        .with_theme(DIALOG_THEME.deref())
+        .run()?;
+    Ok(value)
+}
+
+pub fn read_notes_interactive() -> anyhow::Result<String> {
+    let notes = InputDialog::default()
+        .with_prompt(Prompt::inline(chevron_prompt!("Notes")))
+        .with_placeholder("optional; alt+n for a new line")
+        .with_theme(DIALOG_THEME.deref())
+        .run()?;
+    Ok(notes)
+}
+
+pub fn confirm(message: String) -> anyhow::Result<bool> {
+    let confirmed = YesNoDialog::default()
+        .with_message(message)
+        .with_theme(DIALOG_THEME.deref())
+        .run()?;
+    Ok(confirmed)
+}
+
+/// Lists the entries and directories of the given keys and asks whether to go through with
+/// the destructive action, like `Delete`. Fails without asking unless a terminal is attached, and with
+/// [Cancelled] if the user declines
+pub fn confirm_destructive(store: &Path, action: &str, keys: &[&str]) -> anyhow::Result<()> {
+    require_terminal(&format!("{} {}", action.to_lowercase(), keys.join(", ")))?;
+    let mut entries = 0;
+    for key in keys {
+        let key = key.trim_end_matches('/');
+        if store.join(key).is_dir() {
+            let count = get_existing_keys(store, Some(key))?.len();
+            entries += count;
+            eprintln!("  {key}/ ({count} entries)");
+        } else {
+            entries += 1;
+            eprintln!("  {key}");
+        }
+    }
+    let question = match entries {
+        1 => format!("{action} 1 entry?"),
+        n => format!("{action} {n} entries?"),
+    };
+    confirm_or_cancel(question)
+}
+
+/// Asks whether to overwrite the entry of the given key if there is one, unless `force` is set,
+/// and moves it into the trash, from which it can be restored; returns whether there was one
+pub fn trash_for_overwrite(store: &Path, key: &str, force: bool) -> anyhow::Result<bool> {
+    if !store.join(key).is_file() {
+        return Ok(false);
+    }
+    if !force {
+        confirm_destructive(store, "Overwrite", &[key])?;
+    }
+    trash::trash(store, key)?;
+    Ok(true)
+}
+
+/// Fails unless a terminal is attached on which to confirm the given action, like `delete mail`
+pub fn require_terminal(action: &str) -> anyhow::Result<()> {
+    if io::stdin().is_terminal() && io::stderr().is_terminal() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Refusing to {action} without confirmation; specify --force to skip it"
+        ))
+    }
+}
+
+/// Asks the question and fails with [Cancelled] if the user declines
+pub fn confirm_or_cancel(question: String) -> anyhow::Result<()> {
+    if confirm(question)? {
+        Ok(())
+    } else {
+        Err(Cancelled.into())
+    }
+}
+
+fn validate_strength(min_score: u8) -> impl ValidatorFn<'static> {
+    move |chars| {
+        let password: String = chars.iter().take_while(|&&c| c != '\n').collect();
+        let strength = estimate_strength(&password);
+        if strength.score < min_score {
+            Some(format!("Too weak: {}", describe(strength)).into())
+        } else {
+            None
+        }
+    }
+}
+
 fn match_confirmation() -> Confirmation<'static> {
     Confirmation::new(Prompt::inline(chevron_prompt!("Confirm password  ")))
         .with_validation_message("The entered passwords do not match.")
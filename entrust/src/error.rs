@@ -0,0 +1,114 @@
+use entrust_core::{DecryptError, KeyError};
+use entrust_dialog::dialog::Cancelled;
+use serde::Serialize;
+use std::error::Error;
+use std::io;
+use std::process::ExitCode;
+
+/// The kinds of errors which are distinguished by exit code and in JSON error reports
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorKind {
+    NotFound,
+    DecryptFailed,
+    Cancelled,
+    Ambiguous,
+    Other,
+}
+
+impl ErrorKind {
+    pub fn of(error: &anyhow::Error) -> ErrorKind {
+        error
+            .chain()
+            .find_map(kind_of_cause)
+            .unwrap_or(ErrorKind::Other)
+    }
+
+    /// 2 is left out because clap exits with it on invalid arguments
+    pub fn exit_code(self) -> u8 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::NotFound => 3,
+            ErrorKind::DecryptFailed => 4,
+            ErrorKind::Cancelled => 5,
+            ErrorKind::Ambiguous => 6,
+        }
+    }
+}
+
+fn kind_of_cause(cause: &(dyn Error + 'static)) -> Option<ErrorKind> {
+    if let Some(key_error) = cause.downcast_ref::<KeyError>() {
+        return Some(match key_error {
+            KeyError::NotFound(_) | KeyError::IsDirectory(_) => ErrorKind::NotFound,
+            KeyError::Ambiguous { .. } => ErrorKind::Ambiguous,
+        });
+    }
+    if cause.is::<DecryptError>() {
+        return Some(ErrorKind::DecryptFailed);
+    }
+    let cancelled = cause.is::<Cancelled>()
+        || cause
+            .downcast_ref::<io::Error>()
+            .and_then(|e| e.get_ref())
+            .is_some_and(|e| e.is::<Cancelled>());
+    cancelled.then_some(ErrorKind::Cancelled)
+}
+
+#[derive(Serialize, Debug)]
+struct ErrorReport<'a> {
+    error: ErrorDetails<'a>,
+}
+
+#[derive(Serialize, Debug)]
+struct ErrorDetails<'a> {
+    kind: ErrorKind,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candidates: Option<&'a [String]>,
+}
+
+/// Prints the error to stderr, as a JSON document if requested,
+/// and returns the exit code for its kind
+pub fn report(error: &anyhow::Error, json: bool) -> ExitCode {
+    let kind = ErrorKind::of(error);
+    if json {
+        let candidates = error.chain().find_map(|cause| match cause.downcast_ref() {
+            Some(KeyError::Ambiguous { candidates, .. }) => Some(candidates.as_slice()),
+            _ => None,
+        });
+        let report = ErrorReport {
+            error: ErrorDetails {
+                kind,
+                message: format!("{error:#}"),
+                candidates,
+            },
+        };
+        match serde_json::to_string_pretty(&report) {
+            Ok(report) => eprintln!("{report}"),
+            Err(_) => eprintln!("Error: {error:?}"),
+        }
+    } else {
+        eprintln!("Error: {error:?}");
+    }
+    ExitCode::from(kind.exit_code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kind() {
+        let not_found = anyhow::Error::from(KeyError::NotFound("a".to_string()));
+        assert_eq!(ErrorKind::NotFound, ErrorKind::of(&not_found));
+        let ambiguous = anyhow::Error::from(KeyError::Ambiguous {
+            key: "a".to_string(),
+            candidates: vec!["ab".to_string(), "ac".to_string()],
+        })
+        .context("Could not get a");
+        assert_eq!(ErrorKind::Ambiguous, ErrorKind::of(&ambiguous));
+        let cancelled = anyhow::Error::from(io::Error::other(Cancelled));
+        assert_eq!(ErrorKind::Cancelled, ErrorKind::of(&cancelled));
+        assert_eq!(ErrorKind::Other, ErrorKind::of(&anyhow::anyhow!("other")));
+    }
+}
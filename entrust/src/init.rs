@@ -20,6 +20,8 @@ fn needs_init(subcommand: &EntSubcommand, store: &Path) -> Option<Backend> {
     match subcommand {
         EntSubcommand::Add(args) => Backend::from(args.backend).needs_init(store),
         EntSubcommand::Edit(args) => Backend::from(args.backend).needs_init(store),
+        EntSubcommand::Import(args) => Backend::from(args.backend).needs_init(store),
+        EntSubcommand::Convert(args) => Backend::from(args.to).needs_init(store),
         EntSubcommand::Generate(args) => args.needs_backend().and_then(|b| b.needs_init(store)),
         _ => None,
     }
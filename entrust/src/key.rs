@@ -1,5 +1,12 @@
 use crate::dialog::{read_new_key_interactive, select_existing_key};
-use std::path::Path;
+use anyhow::anyhow;
+use entrust_core::{KeyError, resolve_existing_location};
+use entrust_dialog::dialog::Dialog;
+use entrust_dialog::select::SelectDialog;
+use std::borrow::Cow;
+use std::io;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 
 pub trait Key: Into<Option<String>> {
     fn unwrap_or_read_new(self, prompt: &'static str, store: &Path) -> anyhow::Result<String> {
@@ -17,3 +24,38 @@ pub trait Key: Into<Option<String>> {
 }
 
 impl Key for Option<String> {}
+
+/// Resolves the key of an existing entry like [resolve_existing_location],
+/// but lets the user choose between the candidates if the key is ambiguous and a terminal is attached
+pub fn resolve_existing(store: &Path, key: &str, can_be_dir: bool) -> anyhow::Result<PathBuf> {
+    let error = match resolve_existing_location(store, key, can_be_dir) {
+        Ok(location) => return Ok(location),
+        Err(error) => error,
+    };
+    match error.downcast_ref::<KeyError>() {
+        Some(KeyError::Ambiguous { candidates, .. })
+            if io::stdin().is_terminal() && io::stderr().is_terminal() =>
+        {
+            let options = candidates
+                .iter()
+                .map(|c| Cow::Borrowed(c.as_str()))
+                .collect();
+            match SelectDialog::new(options).run()? {
+                Some(selected) => Ok(store.join(selected.as_ref())),
+                None => Err(error),
+            }
+        }
+        _ => Err(error),
+    }
+}
+
+/// The key of a location in the store
+pub fn key_of(store: &Path, location: &Path) -> anyhow::Result<String> {
+    location
+        .strip_prefix(store)
+        .map(|key| {
+            key.to_string_lossy()
+                .replace(std::path::MAIN_SEPARATOR, "/")
+        })
+        .map_err(|_| anyhow!("{} is not in the store", location.display()))
+}
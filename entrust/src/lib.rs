@@ -1,9 +1,12 @@
 pub mod alias;
 mod animation;
+mod bulk;
 pub mod command;
 mod dialog;
+pub mod error;
 mod init;
 mod key;
+mod strength;
 mod theme;
 mod tree;
 
@@ -0,0 +1,42 @@
+use crate::theme::color;
+use anyhow::anyhow;
+use color_print::ceprintln;
+use entrust_core::{Entry, Strength, estimate_strength};
+use std::io;
+use std::io::IsTerminal;
+
+pub(crate) const MIN_STRENGTH_ENV_VAR: &str = "ENT_MIN_STRENGTH";
+
+pub(crate) fn describe(strength: Strength) -> String {
+    format!(
+        "{} (score {}/{}, ~{:.0} bits of entropy)",
+        strength.label(),
+        strength.score,
+        Strength::MAX_SCORE,
+        strength.entropy
+    )
+}
+
+pub(crate) fn print_strength(strength: Strength) {
+    let description = describe(strength);
+    if color() {
+        ceprintln!("<bright-black>Strength: {description}");
+    } else {
+        eprintln!("Strength: {description}");
+    }
+}
+
+/// Prints the estimated strength of the password in the first line of the content if it was entered
+/// interactively, and fails if it is below the given minimum score
+pub(crate) fn check_strength(content: &str, min_score: Option<u8>) -> anyhow::Result<()> {
+    let strength = estimate_strength(&Entry::parse(content).password);
+    if io::stdin().is_terminal() {
+        print_strength(strength);
+    }
+    match min_score {
+        Some(min_score) if strength.score < min_score => Err(anyhow!(
+            "The password is too weak; a score of at least {min_score} is required"
+        )),
+        _ => Ok(()),
+    }
+}
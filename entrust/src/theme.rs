@@ -11,6 +11,20 @@ pub fn color() -> bool {
     env::var("NO_COLOR").is_err() && io::stdout().is_terminal()
 }
 
+/// Prints a blank line and the text styled like the headers of the help
+pub fn print_heading(text: &str) {
+    if color() {
+        let theme = load_clap_theme();
+        println!(
+            "\n{}{text}{}",
+            theme.get_header().render(),
+            theme.get_header().render_reset()
+        );
+    } else {
+        println!("\n{text}");
+    }
+}
+
 pub static DIALOG_THEME: LazyLock<Theme> = LazyLock::new(load_dialog_theme);
 
 pub fn load_clap_theme() -> Styles {
@@ -1,10 +1,28 @@
 use anyhow::anyhow;
+use serde::Serialize;
 use std::fs;
+use std::fs::DirEntry;
 use std::path::Path;
 
 use crate::theme::{color, load_clap_theme};
 use termtree::Tree;
 
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+enum NodeType {
+    Dir,
+    Entry,
+}
+
+#[derive(Serialize, Debug)]
+struct Node {
+    name: String,
+    #[serde(rename = "type")]
+    node_type: NodeType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<Node>>,
+}
+
 pub fn print_tree(base: &Path) -> anyhow::Result<()> {
     let tree = tree(base)?;
     if color() {
@@ -24,6 +42,14 @@ pub fn print_tree(base: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Prints the store as nested nodes with a name, a type of dir or entry,
+/// and for dirs the children sorted by name
+pub fn print_json_tree(base: &Path) -> anyhow::Result<()> {
+    serde_json::to_writer_pretty(std::io::stdout(), &node(base)?)?;
+    println!();
+    Ok(())
+}
+
 fn label<P: AsRef<Path>>(p: P) -> anyhow::Result<String> {
     p.as_ref()
         .file_name()
@@ -32,8 +58,8 @@ fn label<P: AsRef<Path>>(p: P) -> anyhow::Result<String> {
         .ok_or_else(|| anyhow!("Could not read {:?}", p.as_ref()))
 }
 
-fn tree<P: AsRef<Path>>(p: P) -> anyhow::Result<Tree<String>> {
-    fs::read_dir(&p)?
+fn children<P: AsRef<Path>>(p: P) -> anyhow::Result<impl Iterator<Item = (DirEntry, String)>> {
+    Ok(fs::read_dir(&p)?
         .filter_map(|e| e.ok())
         .filter(|e| {
             e.path()
@@ -42,17 +68,45 @@ fn tree<P: AsRef<Path>>(p: P) -> anyhow::Result<Tree<String>> {
                 .map(|s| !s.starts_with('.'))
                 .unwrap_or(true)
         })
-        .filter_map(|e| label(e.path()).map(|l| (e, l)).ok())
-        .try_fold(
-            Tree::new(label(p.as_ref().canonicalize()?)?),
-            |mut root, (entry, label)| {
-                let dir = entry.metadata()?;
-                if dir.is_dir() {
-                    root.push(tree(entry.path())?);
-                } else {
-                    root.push(Tree::new(label));
-                }
-                Ok(root)
-            },
-        )
+        .filter_map(|e| label(e.path()).map(|l| (e, l)).ok()))
+}
+
+fn node<P: AsRef<Path>>(p: P) -> anyhow::Result<Node> {
+    let mut children = children(&p)?
+        .map(|(entry, name)| {
+            if entry.metadata()?.is_dir() {
+                Ok(Node {
+                    name,
+                    ..node(entry.path())?
+                })
+            } else {
+                Ok(Node {
+                    name,
+                    node_type: NodeType::Entry,
+                    children: None,
+                })
+            }
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Node {
+        name: label(p.as_ref().canonicalize()?)?,
+        node_type: NodeType::Dir,
+        children: Some(children),
+    })
+}
+
+fn tree<P: AsRef<Path>>(p: P) -> anyhow::Result<Tree<String>> {
+    children(&p)?.try_fold(
+        Tree::new(label(p.as_ref().canonicalize()?)?),
+        |mut root, (entry, label)| {
+            let dir = entry.metadata()?;
+            if dir.is_dir() {
+                root.push(tree(entry.path())?);
+            } else {
+                root.push(Tree::new(label));
+            }
+            Ok(root)
+        },
+    )
 }